@@ -2,17 +2,15 @@ use crate::*;
 
 #[test]
 fn test_calculator_lexer() {
-    let mut lexer = Lexer::new("(1+2)*3-4/5");
-    let tokens = lexer.get_tokens();
+    let tokens = parser::get_lexer_tables().tokenize("(1+2)*3-4/5");
     println!("{:?}", tokens);
 }
 
 #[test]
 fn test_calculator_parser() {
-    let mut lexer = Lexer::new("(1+2)*3-4/5");
-    let tokens = lexer.get_tokens().unwrap();
+    let tokens = parser::get_lexer_tables().tokenize("(1+2)*3-4/5").unwrap();
     let tables = parser::get_parse_tables();
-    let tree = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
+    let tree = parser::ParseTree::from_dense_tables_and_tokens(tables, &tokens).unwrap();
     println!("{}", tree.to_graphviz());
 }
 
@@ -22,8 +20,29 @@ fn test_calculator_eval_ok() {
     assert_eq!(res.unwrap(), 8.2);
 }
 
+#[test]
+fn test_calculator_eval_associativity() {
+    assert_eq!(evaluate_from_string("1-2+3").unwrap(), 2.0);
+    assert_eq!(evaluate_from_string("-2*3-8/2/2").unwrap(), -8.0);
+}
+
 #[test]
 fn test_calculator_eval_zero_division() {
     let res = evaluate_from_string("(1+2)*3-4/0");
     assert!(res.is_err());
 }
+
+#[test]
+fn test_calculator_eval_lexer_error() {
+    let res = evaluate_from_string("1 + x");
+    assert_eq!(res.unwrap_err().to_string(), "Unexpected character at 1:5");
+}
+
+#[test]
+fn test_calculator_eval_parser_error() {
+    let res = evaluate_from_string("(1+2");
+    assert_eq!(
+        res.unwrap_err().to_string(),
+        "Unexpected token EOF at 1:5-1:5, expected one of `)`, `*`, `+`, `-`, `/`"
+    );
+}
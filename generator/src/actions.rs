@@ -0,0 +1,363 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::parser::*;
+use crate::{GeneratorError, Grammar, GRAMMAR_AXIOM_NAME};
+
+const DEFAULT_ATTRIBUTE_TYPE: &str = "Lexeme";
+
+/// Rust code attached to a grammar: the type of token attributes,
+/// types of nonterminals and actions of rules.
+#[derive(PartialEq, Eq, Debug, Default)]
+pub struct Semantics {
+    pub(crate) attribute: Option<String>,
+    pub(crate) types: HashMap<Nonterminal, String>,
+    pub(crate) actions: HashMap<Rule, String>,
+}
+
+impl Grammar {
+    pub fn has_actions(&self) -> bool {
+        self.semantics.attribute.is_some()
+            || !self.semantics.types.is_empty()
+            || !self.semantics.actions.is_empty()
+    }
+
+    /// Emits `parse()`, which evaluates the actions of reduced rules instead of building
+    /// a `ParseTree`. `$k` in an action is the value of the k-th symbol of the alternative:
+    /// the attribute of a terminal, the value of a nonterminal, `Option` for `?`,
+    /// `Vec` for `*` and `+`, a tuple for a group.
+    pub fn actions_to_rust_source(&self) -> Result<String, GeneratorError> {
+        ActionsWriter::new(self, "ParseTables").write()
+    }
+
+    /// Like `actions_to_rust_source`, but `parse()` takes `DenseParseTables`.
+    pub fn dense_actions_to_rust_source(&self) -> Result<String, GeneratorError> {
+        ActionsWriter::new(self, "DenseParseTables").write()
+    }
+
+    /// Like `actions_to_rust_source`, but `parse()` takes `LL1ParseTables`.
+    pub fn ll1_actions_to_rust_source(&self) -> Result<String, GeneratorError> {
+        ActionsWriter::new(self, "LL1ParseTables").write()
+    }
+
+    pub(crate) fn get_attribute_type(&self) -> &str {
+        match &self.semantics.attribute {
+            Some(t) => t.as_str(),
+            None => DEFAULT_ATTRIBUTE_TYPE,
+        }
+    }
+}
+
+struct ActionsWriter<'a> {
+    grammar: &'a Grammar,
+    rules: Vec<&'a Rule>,
+    variants: HashMap<Nonterminal, String>,
+    types: HashMap<Nonterminal, String>,
+    attribute: &'a str,
+    /// type of the tables taken by `parse()`
+    tables_type: &'static str,
+}
+
+impl<'a> ActionsWriter<'a> {
+    fn new(grammar: &'a Grammar, tables_type: &'static str) -> Self {
+        let rules: Vec<&Rule> = grammar
+            .rules
+            .iter()
+            .filter(|x| x.left.0 != GRAMMAR_AXIOM_NAME)
+            .collect();
+        let mut variants = HashMap::new();
+        let mut used = HashSet::from([String::from("Token")]);
+        for rule in &rules {
+            if variants.contains_key(&rule.left) {
+                continue;
+            }
+            let mut name = rust_identifier(&rule.left.0);
+            if used.contains(&name) {
+                name = format!("{}_{}", name, used.len());
+            }
+            used.insert(name.clone());
+            variants.insert(rule.left.clone(), name);
+        }
+        let attribute = grammar.get_attribute_type();
+        let mut writer = ActionsWriter {
+            grammar,
+            rules,
+            variants,
+            types: HashMap::new(),
+            attribute,
+            tables_type,
+        };
+        for rule in writer.rules.clone() {
+            writer.get_nonterminal_type(&rule.left);
+        }
+        writer
+    }
+
+    fn write(&self) -> Result<String, GeneratorError> {
+        let mut variants = String::new();
+        let mut nonterminals: Vec<(&Nonterminal, &String)> = self.variants.iter().collect();
+        nonterminals.sort_by_key(|(_, variant)| *variant);
+        for (nterm, variant) in nonterminals {
+            variants += format!("{}({}),\n", variant, self.types[nterm]).as_ref();
+        }
+        let mut rules = String::new();
+        let mut arms = String::new();
+        let mut functions = String::new();
+        for (i, rule) in self.rules.iter().enumerate() {
+            rules += format!("{},\n", rule.to_literal()).as_ref();
+            arms += self.write_reduce_arm(i, rule).as_ref();
+            functions += self.write_action(i, rule)?.as_ref();
+        }
+        let axiom = &self.grammar.axiom;
+        let tables_type = self.tables_type;
+        let reduce = if tables_type == "DenseParseTables" {
+            "let ids: Vec<usize> = (0..tables.rules.len())
+                    .map(|i| rules[&tables.get_rule(i)])
+                    .collect();
+                let value = tables.parse_with(
+                    tokens,
+                    |token| SemanticValue::Token(token.attribute.clone()),
+                    |rule, values| reduce(ids[rule], values),
+                )?;"
+        } else {
+            "let value = tables.parse_with(
+                    tokens,
+                    |token| SemanticValue::Token(token.attribute.clone()),
+                    |rule, values| reduce(rules[rule], values),
+                )?;"
+        };
+        Ok(format!(
+            r#"
+        #[allow(non_camel_case_types)]
+        pub enum SemanticValue {{
+            Token({attribute}),
+            {variants}
+        }}
+
+        pub fn get_rules() -> Vec<Rule> {{
+            vec![
+                {rules}
+            ]
+        }}
+
+        pub fn parse(
+            tables: &{tables_type},
+            tokens: &[Token<{attribute}>],
+        ) -> Result<{axiom_type}, ParseError<{attribute}>> {{
+            let rules: HashMap<Rule, usize> = get_rules()
+                .into_iter()
+                .enumerate()
+                .map(|(i, rule)| (rule, i))
+                .collect();
+            {reduce}
+            let SemanticValue::{axiom_variant}(value) = value else {{
+                unreachable!()
+            }};
+            Ok(value)
+        }}
+
+        fn reduce(rule: usize, values: Vec<SemanticValue>) -> SemanticValue {{
+            let mut values = values.into_iter();
+            match rule {{
+                {arms}
+                _ => unreachable!(),
+            }}
+        }}
+
+        {functions}
+        "#,
+            attribute = self.attribute,
+            tables_type = tables_type,
+            reduce = reduce,
+            variants = variants,
+            rules = rules,
+            axiom_type = self.types[axiom],
+            axiom_variant = self.variants[axiom],
+            arms = arms.trim_end(),
+            functions = functions,
+        ))
+    }
+
+    fn write_reduce_arm(&self, index: usize, rule: &Rule) -> String {
+        let mut res = String::new();
+        let mut args = Vec::new();
+        for (i, term) in rule.right.iter().enumerate() {
+            let name = format!("_v{}", i + 1);
+            let (variant, is_foreign_list) = match term {
+                Term::Terminal(_) => ("Token", false),
+                Term::Nonterminal(nterm) => (
+                    self.variants[nterm].as_str(),
+                    is_list(nterm) && list_name(nterm) != list_name(&rule.left),
+                ),
+            };
+            if is_foreign_list {
+                // lists are built in reverse order by right-recursive rules
+                res += format!(
+                    "let Some(SemanticValue::{}(mut {})) = values.next() else {{ unreachable!() }};\n",
+                    variant, name
+                )
+                .as_ref();
+                res += format!("{}.reverse();\n", name).as_ref();
+            } else {
+                res += format!(
+                    "let Some(SemanticValue::{}({})) = values.next() else {{ unreachable!() }};\n",
+                    variant, name
+                )
+                .as_ref();
+            }
+            args.push(name);
+        }
+        let call = format!("action_{}({})", index, args.join(", "));
+        let variant = &self.variants[&rule.left];
+        if self.types[&rule.left] == "()" {
+            res += format!("{};\nSemanticValue::{}(())", call, variant).as_ref();
+        } else {
+            res += format!("SemanticValue::{}({})", variant, call).as_ref();
+        }
+        format!("{} => {{\n{}\n}}\n", index, res)
+    }
+
+    fn write_action(&self, index: usize, rule: &Rule) -> Result<String, GeneratorError> {
+        let body = match self.grammar.semantics.actions.get(rule) {
+            Some(code) => substitute_references(code, rule, self.grammar)?,
+            None => self.get_default_action(rule)?,
+        };
+        let params: Vec<String> = rule
+            .right
+            .iter()
+            .enumerate()
+            .map(|(i, term)| format!("_v{}: {}", i + 1, self.get_term_type(term)))
+            .collect();
+        let allow = if params.len() > 7 {
+            "#[allow(clippy::too_many_arguments)]\n"
+        } else {
+            ""
+        };
+        let return_type = match self.types[&rule.left].as_str() {
+            "()" => String::new(),
+            t => format!(" -> {}", t),
+        };
+        Ok(format!(
+            "{}fn action_{}({}){} {{\n{}\n}}\n\n",
+            allow,
+            index,
+            params.join(", "),
+            return_type,
+            body
+        ))
+    }
+
+    fn get_default_action(&self, rule: &Rule) -> Result<String, GeneratorError> {
+        let nterm = &rule.left;
+        if !nterm.is_generated() {
+            if self.grammar.semantics.types.contains_key(nterm) {
+                let fragment = self.grammar.get_rule_fragment(rule);
+                return Err(GeneratorError::MissingAction(rule.clone(), fragment));
+            }
+            return Ok(String::new());
+        }
+        let res = match (nterm.0.chars().last(), rule.right.len()) {
+            (Some('?'), 0) => String::from("None"),
+            (Some('?'), _) => String::from("Some(_v1)"),
+            (Some('*'), 0) => String::from("Vec::new()"),
+            (Some('*') | Some('+'), _) => {
+                String::from("let mut list = _v2;\nlist.push(_v1);\nlist")
+            }
+            (_, 0) => String::new(),
+            (_, n) => {
+                let items: Vec<String> = (1..=n).map(|i| format!("_v{}", i)).collect();
+                format!("({})", items.join(", "))
+            }
+        };
+        Ok(res)
+    }
+
+    fn get_term_type(&self, term: &Term) -> String {
+        match term {
+            Term::Terminal(_) => String::from(self.attribute),
+            Term::Nonterminal(nterm) => self.types[nterm].clone(),
+        }
+    }
+
+    fn get_nonterminal_type(&mut self, nterm: &Nonterminal) -> String {
+        if let Some(t) = self.types.get(nterm) {
+            return t.clone();
+        }
+        let res = if !nterm.is_generated() {
+            match self.grammar.semantics.types.get(nterm) {
+                Some(t) => t.clone(),
+                None => String::from("()"),
+            }
+        } else {
+            let right = self
+                .rules
+                .iter()
+                .filter(|x| x.left == *nterm)
+                .map(|x| &x.right)
+                .max_by_key(|x| x.len())
+                .unwrap()
+                .clone();
+            let is_group = !matches!(nterm.0.chars().last(), Some('?' | '*' | '+'));
+            // the rest of a list rule is the list itself
+            let count = if is_group { right.len() } else { 1 };
+            let mut items = Vec::new();
+            for term in &right[..count] {
+                items.push(match term {
+                    Term::Terminal(_) => String::from(self.attribute),
+                    Term::Nonterminal(other) => self.get_nonterminal_type(other),
+                });
+            }
+            match nterm.0.chars().last() {
+                Some('?') => format!("Option<{}>", items[0]),
+                Some('*') | Some('+') => format!("Vec<{}>", items[0]),
+                _ if items.len() == 1 => format!("({},)", items[0]),
+                _ => format!("({})", items.join(", ")),
+            }
+        };
+        self.types.insert(nterm.clone(), res.clone());
+        res
+    }
+}
+
+/// Replaces `$k` with the name of the k-th parameter of the action function.
+fn substitute_references(
+    code: &str,
+    rule: &Rule,
+    grammar: &Grammar,
+) -> Result<String, GeneratorError> {
+    let mut res = String::new();
+    let mut chars = code.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' || !chars.peek().is_some_and(|x| x.is_ascii_digit()) {
+            res.push(c);
+            continue;
+        }
+        let mut index = 0;
+        while let Some(digit) = chars.peek().and_then(|x| x.to_digit(10)) {
+            index = index * 10 + digit as usize;
+            chars.next();
+        }
+        if index == 0 || index > rule.right.len() {
+            return Err(GeneratorError::InvalidActionReference {
+                rule: rule.clone(),
+                index,
+                fragment: grammar.get_rule_fragment(rule),
+            });
+        }
+        res += format!("_v{}", index).as_ref();
+    }
+    Ok(res)
+}
+
+fn is_list(nterm: &Nonterminal) -> bool {
+    nterm.is_generated() && (nterm.0.ends_with('*') || nterm.0.ends_with('+'))
+}
+
+fn list_name(nterm: &Nonterminal) -> &str {
+    nterm.0.trim_end_matches(['*', '+'])
+}
+
+pub(crate) fn rust_identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
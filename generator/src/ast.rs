@@ -0,0 +1,307 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::actions::rust_identifier;
+use crate::parser::*;
+use crate::{Grammar, GRAMMAR_AXIOM_NAME};
+
+impl Grammar {
+    /// Emits the `ast` module with an enum per nonterminal of the grammar and a variant
+    /// `AltK` per its alternative. Fields are `Token` for terminals, `Box` for nonterminals,
+    /// `Option` for `?`, `Vec` for `*` and `+`, tuples for groups and skipped tokens
+    /// for `error`. `from_tree` converts a `ParseTree` into the typed tree.
+    pub fn ast_to_rust_source(&self) -> String {
+        AstWriter::new(self).write()
+    }
+}
+
+struct AstWriter<'a> {
+    rules: Vec<&'a Rule>,
+    names: HashMap<Nonterminal, String>,
+    attribute: &'a str,
+}
+
+impl<'a> AstWriter<'a> {
+    fn new(grammar: &'a Grammar) -> Self {
+        let rules: Vec<&Rule> = grammar
+            .rules
+            .iter()
+            .filter(|x| x.left.0 != GRAMMAR_AXIOM_NAME)
+            .collect();
+        let mut names = HashMap::new();
+        let mut used = HashSet::new();
+        let mut generated = 0;
+        for rule in &rules {
+            if names.contains_key(&rule.left) {
+                continue;
+            }
+            let name = if rule.left.is_generated() {
+                generated += 1;
+                format!("convert_{}", generated - 1)
+            } else {
+                let mut name = rust_identifier(&rule.left.0);
+                if used.contains(&name) {
+                    name = format!("{}_{}", name, used.len());
+                }
+                used.insert(name.clone());
+                name
+            };
+            names.insert(rule.left.clone(), name);
+        }
+        AstWriter {
+            rules,
+            names,
+            attribute: grammar.get_attribute_type(),
+        }
+    }
+
+    fn write(&self) -> String {
+        let mut items = String::new();
+        let mut written = HashSet::new();
+        for rule in &self.rules {
+            if !written.insert(&rule.left) {
+                continue;
+            }
+            if rule.left.is_generated() {
+                items += self.write_converter(&rule.left).as_ref();
+            } else {
+                items += self.write_enum(&rule.left).as_ref();
+            }
+        }
+        let has_error = self
+            .rules
+            .iter()
+            .flat_map(|x| &x.right)
+            .any(is_error_terminal);
+        let skipped = if has_error {
+            format!(
+                r#"
+            fn skipped(tree: super::ParseTree<{attribute}>) -> Option<Vec<super::Token<{attribute}>>> {{
+                match tree {{
+                    super::ParseTree::Error(tokens) => Some(tokens),
+                    super::ParseTree::Internal(_, _) | super::ParseTree::Leaf(_) => None,
+                }}
+            }}
+            "#,
+                attribute = self.attribute
+            )
+        } else {
+            String::new()
+        };
+        format!(
+            r#"
+        #[allow(non_camel_case_types, non_snake_case, dead_code, clippy::enum_variant_names, clippy::large_enum_variant)]
+        pub mod ast {{
+            use super::*;
+
+            {items}
+
+            fn symbol(tree: &super::ParseTree<{attribute}>) -> String {{
+                match tree {{
+                    super::ParseTree::Internal(nterm, _) => nterm.0.clone(),
+                    super::ParseTree::Leaf(token) => token.tag.to_string(),
+                    super::ParseTree::Error(_) => String::from(super::ERROR_TERMINAL_NAME),
+                }}
+            }}
+
+            fn leaf(tree: super::ParseTree<{attribute}>) -> Option<super::Token<{attribute}>> {{
+                match tree {{
+                    super::ParseTree::Leaf(token) => Some(token),
+                    super::ParseTree::Internal(_, _) | super::ParseTree::Error(_) => None,
+                }}
+            }}
+            {skipped}
+        }}
+        "#,
+            items = items,
+            skipped = skipped,
+            attribute = self.attribute,
+        )
+    }
+
+    fn write_enum(&self, nterm: &Nonterminal) -> String {
+        let name = &self.names[nterm];
+        let alternatives: Vec<&Rule> = self
+            .rules
+            .iter()
+            .filter(|x| x.left == *nterm)
+            .copied()
+            .collect();
+        let mut variants = String::new();
+        let mut arms = String::new();
+        for (i, rule) in alternatives.iter().enumerate() {
+            let types: Vec<String> = rule.right.iter().map(|x| self.get_type(x, true)).collect();
+            let values: Vec<String> = rule
+                .right
+                .iter()
+                .map(|x| self.get_conversion(x, true, "children.next()?"))
+                .collect();
+            let symbols: Vec<String> = rule
+                .right
+                .iter()
+                .map(|x| format!("{:?}", x.to_string()))
+                .collect();
+            if rule.right.is_empty() {
+                variants += format!("Alt{},\n", i).as_ref();
+                arms += format!("[] => {}::Alt{},\n", name, i).as_ref();
+            } else {
+                variants += format!("Alt{}({}),\n", i, types.join(", ")).as_ref();
+                arms += format!(
+                    "[{}] => {}::Alt{}({}),\n",
+                    symbols.join(", "),
+                    name,
+                    i,
+                    values.join(", ")
+                )
+                .as_ref();
+            }
+        }
+        let children = if alternatives.iter().any(|x| !x.right.is_empty()) {
+            "let mut children = children.into_iter();"
+        } else {
+            ""
+        };
+        format!(
+            r#"
+            /// `{nterm}`
+            #[derive(Debug, Clone)]
+            pub enum {name} {{
+                {variants}
+            }}
+
+            impl {name} {{
+                pub fn from_tree(tree: super::ParseTree<{attribute}>) -> Option<{name}> {{
+                    let super::ParseTree::Internal(nterm, children) = tree else {{
+                        return None;
+                    }};
+                    if nterm.0 != {nterm:?} {{
+                        return None;
+                    }}
+                    let symbols: Vec<String> = children.iter().map(symbol).collect();
+                    let symbols: Vec<&str> = symbols.iter().map(String::as_str).collect();
+                    {children}
+                    let res = match symbols[..] {{
+                        {arms}
+                        _ => return None,
+                    }};
+                    Some(res)
+                }}
+            }}
+            "#,
+            nterm = nterm.0,
+            name = name,
+            variants = variants,
+            attribute = self.attribute,
+            arms = arms,
+            children = children,
+        )
+    }
+
+    fn write_converter(&self, nterm: &Nonterminal) -> String {
+        let right = self
+            .rules
+            .iter()
+            .filter(|x| x.left == *nterm)
+            .map(|x| &x.right)
+            .max_by_key(|x| x.len())
+            .unwrap();
+        let body = match nterm.0.chars().last() {
+            Some('?') => format!(
+                r#"
+                match children.into_iter().next() {{
+                    None => Some(None),
+                    Some(child) => Some(Some({})),
+                }}
+                "#,
+                self.get_conversion(&right[0], true, "child")
+            ),
+            Some('*') | Some('+') => format!(
+                "children.into_iter().map({}).collect()",
+                self.get_converter(&right[0])
+            ),
+            _ => {
+                let values: Vec<String> = right
+                    .iter()
+                    .map(|x| self.get_conversion(x, true, "children.next()?"))
+                    .collect();
+                format!(
+                    "let mut children = children.into_iter();\nSome(({},))",
+                    values.join(", ")
+                )
+            }
+        };
+        format!(
+            r#"
+            /// `{nterm}`
+            fn {name}(tree: super::ParseTree<{attribute}>) -> Option<{ty}> {{
+                let super::ParseTree::Internal(_, children) = tree else {{
+                    return None;
+                }};
+                {body}
+            }}
+            "#,
+            nterm = nterm.0,
+            name = self.names[nterm],
+            attribute = self.attribute,
+            ty = self.get_type(&Term::Nonterminal(nterm.clone()), true),
+            body = body,
+        )
+    }
+
+    fn get_type(&self, term: &Term, boxed: bool) -> String {
+        let nterm = match term {
+            Term::Terminal(_) if is_error_terminal(term) => {
+                return format!("Vec<super::Token<{}>>", self.attribute)
+            }
+            Term::Terminal(_) => return format!("super::Token<{}>", self.attribute),
+            Term::Nonterminal(nterm) => nterm,
+        };
+        if !nterm.is_generated() {
+            let name = &self.names[nterm];
+            return if boxed {
+                format!("Box<{}>", name)
+            } else {
+                name.clone()
+            };
+        }
+        let right = self
+            .rules
+            .iter()
+            .filter(|x| x.left == *nterm)
+            .map(|x| &x.right)
+            .max_by_key(|x| x.len())
+            .unwrap();
+        match nterm.0.chars().last() {
+            Some('?') => format!("Option<{}>", self.get_type(&right[0], true)),
+            Some('*') | Some('+') => format!("Vec<{}>", self.get_type(&right[0], false)),
+            _ => {
+                let types: Vec<String> = right.iter().map(|x| self.get_type(x, true)).collect();
+                format!("({},)", types.join(", "))
+            }
+        }
+    }
+
+    /// Returns the function converting a tree of the symbol `term`.
+    fn get_converter(&self, term: &Term) -> String {
+        match term {
+            Term::Terminal(_) if is_error_terminal(term) => String::from("skipped"),
+            Term::Terminal(_) => String::from("leaf"),
+            Term::Nonterminal(nterm) if nterm.is_generated() => self.names[nterm].clone(),
+            Term::Nonterminal(nterm) => format!("{}::from_tree", self.names[nterm]),
+        }
+    }
+
+    /// Returns an expression converting the tree `value` of the symbol `term`.
+    fn get_conversion(&self, term: &Term, boxed: bool, value: &str) -> String {
+        let res = format!("{}({})?", self.get_converter(term), value);
+        match term {
+            Term::Nonterminal(nterm) if boxed && !nterm.is_generated() => {
+                format!("Box::new({})", res)
+            }
+            _ => res,
+        }
+    }
+}
+
+fn is_error_terminal(term: &Term) -> bool {
+    matches!(term, Term::Terminal(t) if t.0 == ERROR_TERMINAL_NAME)
+}
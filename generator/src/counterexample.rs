@@ -0,0 +1,681 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::Display,
+};
+
+use crate::parser::*;
+use crate::symbols::{Item, SymbolTable};
+use crate::{
+    Conflict, DetermenisticLR1Automaton, LR1Item, NonDeterministicLR1Automaton, GRAMMAR_AXIOM_NAME,
+};
+
+/// Maximum number of configurations visited by the joint search for an input
+/// shared by all actions of a conflict.
+const JOINT_SEARCH_LIMIT: usize = 1_000;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ConflictAction {
+    Shift,
+    Reduce,
+}
+
+/// Parse of an input prefix that ends right before the conflicting lookahead.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Counterexample {
+    pub action: ConflictAction,
+    pub prefix: Vec<Term>,
+    /// items whose nonterminals after the dot derive the item, outermost first
+    pub parents: Vec<LR1Item>,
+    pub item: LR1Item,
+    pub lookahead: TerminalOrFinish,
+    pub input: Vec<Terminal>,
+}
+
+/// Item that one of the actions of a conflict comes from.
+struct Goal<'b> {
+    action: ConflictAction,
+    rule: &'b Rule,
+    /// `None` matches any lookahead of a reduced item
+    lookahead: Option<u32>,
+}
+
+/// Item paths from a start item, each step is a shift of the symbol or
+/// a closure step if the symbol is `None`.
+type Path = Vec<(Item, Option<SymbolId>)>;
+/// previous node and the shifted symbol, `None` for closure steps
+type Parent = Option<(usize, Option<SymbolId>)>;
+
+/// Graph of automaton states paired with items valid in them with exact lookaheads,
+/// and the shortest paths to its nodes, shared between all conflicts of an automaton.
+pub struct CounterexampleSearch<'a> {
+    symbols: &'a SymbolTable,
+    states: Vec<&'a BTreeSet<Item>>,
+    /// the first `starts` nodes are the items of the fake axiom in the start states
+    nodes: Vec<(usize, Item)>,
+    starts: usize,
+    /// shifts of productive symbols and closure steps, labeled with `None`
+    edges: Vec<Vec<(usize, Option<SymbolId>)>>,
+    reverse_edges: Vec<Vec<usize>>,
+    /// number of shifts on the shortest path from a start node and the previous node
+    distances: Vec<usize>,
+    parents: Vec<Parent>,
+    yields: HashMap<Nonterminal, Vec<Terminal>>,
+}
+
+impl<'a> CounterexampleSearch<'a> {
+    pub fn new(automaton: &'a DetermenisticLR1Automaton) -> Self {
+        let symbols = &automaton.symbols;
+        let yields = get_shortest_yields(symbols);
+        let first = NonDeterministicLR1Automaton::get_first_ids(symbols);
+        let states: Vec<&BTreeSet<Item>> = automaton.edges.keys().collect();
+        let state_ids: HashMap<&BTreeSet<Item>, usize> =
+            states.iter().enumerate().map(|(i, x)| (*x, i)).collect();
+        let gotos: Vec<HashMap<SymbolId, usize>> = states
+            .iter()
+            .map(|state| {
+                automaton.edges[*state]
+                    .iter()
+                    .map(|(other, symbol)| (*symbol, state_ids[other]))
+                    .collect()
+            })
+            .collect();
+        let is_productive = |symbol: SymbolId| match symbol {
+            SymbolId::Terminal(_) => true,
+            SymbolId::Nonterminal(n) => yields.contains_key(symbols.nonterminal(n as u32)),
+        };
+        let start_items = NonDeterministicLR1Automaton::get_start_items(symbols);
+        let mut nodes: Vec<(usize, Item)> = Vec::new();
+        for state in [&automaton.start].into_iter().chain(&automaton.entries) {
+            if let Some(item) = start_items.iter().find(|x| state.contains(x)) {
+                nodes.push((state_ids[state], *item));
+            }
+        }
+        let starts = nodes.len();
+        let mut index: HashMap<(usize, Item), usize> =
+            nodes.iter().enumerate().map(|(i, x)| (*x, i)).collect();
+        // nodes are numbered in the order of the queue, so edges are pushed by node
+        let mut edges = Vec::new();
+        let mut cur = 0;
+        while cur < nodes.len() {
+            let (state, item) = nodes[cur];
+            let mut adjacent = Vec::new();
+            for (other, symbol) in
+                NonDeterministicLR1Automaton::get_adjacent(symbols, &first, &item)
+            {
+                let other_state = match symbol {
+                    None => state,
+                    Some(symbol) if is_productive(symbol) => match gotos[state].get(&symbol) {
+                        Some(other_state) => *other_state,
+                        None => continue,
+                    },
+                    Some(_) => continue,
+                };
+                let id = *index.entry((other_state, other)).or_insert_with(|| {
+                    nodes.push((other_state, other));
+                    nodes.len() - 1
+                });
+                adjacent.push((id, symbol));
+            }
+            edges.push(adjacent);
+            cur += 1;
+        }
+        let mut reverse_edges = vec![Vec::new(); nodes.len()];
+        for (i, adjacent) in edges.iter().enumerate() {
+            for (other, _) in adjacent {
+                reverse_edges[*other].push(i);
+            }
+        }
+        let (distances, parents) = get_shortest_paths(&edges, starts);
+        CounterexampleSearch {
+            symbols,
+            states,
+            nodes,
+            starts,
+            edges,
+            reverse_edges,
+            distances,
+            parents,
+            yields,
+        }
+    }
+
+    /// Looks for an input prefix shared by all actions of the conflict, if
+    /// there is none, each action gets its own shortest prefix.
+    pub fn find_all(
+        &self,
+        ids: &HashMap<&BTreeSet<Item>, i32>,
+        conflict: &Conflict,
+    ) -> Vec<Counterexample> {
+        let lookahead = match &conflict.lookahead {
+            TerminalOrFinish::Terminal(t) => self.symbols.get_terminal_id(t),
+            TerminalOrFinish::Finish => Some(0),
+        };
+        let mut goals = Vec::new();
+        for rule in &conflict.shift {
+            goals.push(Goal {
+                action: ConflictAction::Shift,
+                rule,
+                lookahead,
+            });
+        }
+        for rule in &conflict.reduce {
+            goals.push(Goal {
+                action: ConflictAction::Reduce,
+                rule,
+                lookahead,
+            });
+        }
+        let states: Vec<usize> = (0..self.states.len())
+            .filter(|x| ids[self.states[*x]] == conflict.state)
+            .collect();
+        let find_nodes = |goal: &Goal| -> Vec<usize> {
+            (0..self.nodes.len())
+                .filter(|x| {
+                    let (state, item) = &self.nodes[*x];
+                    states.contains(state) && self.matches(goal, item)
+                })
+                .collect()
+        };
+        let targets: Vec<Vec<usize>> = goals.iter().map(find_nodes).collect();
+        let mut paths = Vec::new();
+        for (goal, nodes) in goals.iter().zip(&targets) {
+            let mut nodes = nodes.clone();
+            // lookaheads of SLR and LR(0) items are not exact
+            if nodes.is_empty() && goal.action == ConflictAction::Reduce {
+                nodes = find_nodes(&Goal {
+                    action: ConflictAction::Reduce,
+                    rule: goal.rule,
+                    lookahead: None,
+                });
+            }
+            let node = nodes.into_iter().min_by_key(|x| self.distances[*x]);
+            paths.push(node.map(|x| self.get_path(x)));
+        }
+        if let Some(shared) = self.find_shared(&targets, &paths) {
+            paths = shared.into_iter().map(Some).collect();
+        }
+        goals
+            .iter()
+            .zip(paths)
+            .filter_map(|(goal, path)| Some(self.to_counterexample(goal, &path?)))
+            .collect()
+    }
+
+    /// Tries the shortest prefix of each action for the other actions, then
+    /// searches for a common prefix of all of them.
+    fn find_shared(&self, targets: &[Vec<usize>], paths: &[Option<Path>]) -> Option<Vec<Path>> {
+        for path in paths.iter().flatten() {
+            let prefix: Vec<SymbolId> = path.iter().filter_map(|x| x.1).collect();
+            let shared: Option<Vec<Path>> =
+                targets.iter().map(|x| self.follow(&prefix, x)).collect();
+            if shared.is_some() {
+                return shared;
+            }
+        }
+        self.search_joint(targets)
+    }
+
+    /// Shortest path to one of the target nodes that shifts exactly the prefix.
+    fn follow(&self, prefix: &[SymbolId], targets: &[usize]) -> Option<Path> {
+        // previous node of each node reached after `i` shifts, `None` for start nodes
+        let mut parents: Vec<BTreeMap<usize, Parent>> = Vec::new();
+        let mut layer: Vec<usize> = (0..self.starts).collect();
+        let mut reached: BTreeMap<usize, Parent> = layer.iter().map(|x| (*x, None)).collect();
+        for i in 0..=prefix.len() {
+            let mut queue = VecDeque::from(layer);
+            while let Some(cur) = queue.pop_front() {
+                for (other, symbol) in &self.edges[cur] {
+                    if symbol.is_none() && !reached.contains_key(other) {
+                        reached.insert(*other, Some((cur, None)));
+                        queue.push_back(*other);
+                    }
+                }
+            }
+            if i == prefix.len() {
+                let mut node = targets.iter().copied().find(|x| reached.contains_key(x))?;
+                parents.push(reached);
+                let mut res = Vec::new();
+                for reached in parents.iter().rev() {
+                    while let Some((prev, symbol)) = reached[&node] {
+                        res.push((self.nodes[node].1, symbol));
+                        node = prev;
+                        if symbol.is_some() {
+                            break;
+                        }
+                    }
+                }
+                res.push((self.nodes[node].1, None));
+                res.reverse();
+                return Some(res);
+            }
+            let mut next = BTreeMap::new();
+            for cur in reached.keys() {
+                for (other, symbol) in &self.edges[*cur] {
+                    if *symbol == Some(prefix[i]) {
+                        next.entry(*other).or_insert(Some((*cur, *symbol)));
+                    }
+                }
+            }
+            layer = next.keys().copied().collect();
+            parents.push(std::mem::replace(&mut reached, next));
+        }
+        None
+    }
+
+    /// Breadth-first search over configurations of one node per goal, the nodes are
+    /// shifted together, so they share the state and the prefix. Only nodes that can
+    /// reach their goals are kept, at most `JOINT_SEARCH_LIMIT` configurations are visited.
+    fn search_joint(&self, targets: &[Vec<usize>]) -> Option<Vec<Path>> {
+        let useful: Vec<HashSet<usize>> = targets.iter().map(|x| self.get_ancestors(x)).collect();
+        let mut closures: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut configs: Vec<Vec<usize>> = Vec::new();
+        // previous configuration, shifted symbol and nodes before the shift
+        let mut parents: Vec<Option<(usize, SymbolId, Vec<usize>)>> = Vec::new();
+        let mut visited: HashSet<Vec<usize>> = HashSet::new();
+        let mut layer = Vec::new();
+        for start in 0..self.starts {
+            let config = vec![start; targets.len()];
+            if useful.iter().all(|x| x.contains(&start)) && visited.insert(config.clone()) {
+                layer.push(configs.len());
+                configs.push(config);
+                parents.push(None);
+            }
+        }
+        while !layer.is_empty() {
+            let mut next_layer = Vec::new();
+            for cur in layer {
+                let closed: Vec<Vec<usize>> = configs[cur]
+                    .iter()
+                    .zip(&useful)
+                    .map(|(node, useful)| {
+                        let closure = closures
+                            .entry(*node)
+                            .or_insert_with(|| self.get_closure(*node));
+                        closure
+                            .iter()
+                            .copied()
+                            .filter(|x| useful.contains(x))
+                            .collect()
+                    })
+                    .collect();
+                let found: Option<Vec<usize>> = closed
+                    .iter()
+                    .zip(targets)
+                    .map(|(nodes, targets)| nodes.iter().copied().find(|x| targets.contains(x)))
+                    .collect();
+                if let Some(found) = found {
+                    return Some(self.get_joint_paths(&configs, &parents, cur, &found));
+                }
+                // shifted nodes of each goal by symbol, as pairs of the nodes before and after
+                let mut shifts: BTreeMap<SymbolId, Vec<Vec<(usize, usize)>>> = BTreeMap::new();
+                for (i, nodes) in closed.iter().enumerate() {
+                    for node in nodes {
+                        for (other, symbol) in &self.edges[*node] {
+                            let Some(symbol) = symbol else {
+                                continue;
+                            };
+                            let by_goal = shifts
+                                .entry(*symbol)
+                                .or_insert_with(|| vec![Vec::new(); closed.len()]);
+                            by_goal[i].push((*node, *other));
+                        }
+                    }
+                }
+                for (symbol, by_goal) in shifts {
+                    let count = by_goal
+                        .iter()
+                        .fold(1usize, |x, y| x.saturating_mul(y.len()));
+                    if configs.len().saturating_add(count) > JOINT_SEARCH_LIMIT {
+                        return None;
+                    }
+                    for pairs in get_product(&by_goal) {
+                        let config: Vec<usize> = pairs.iter().map(|x| x.1).collect();
+                        if !visited.insert(config.clone()) {
+                            continue;
+                        }
+                        next_layer.push(configs.len());
+                        configs.push(config);
+                        parents.push(Some((cur, symbol, pairs.iter().map(|x| x.0).collect())));
+                    }
+                }
+            }
+            layer = next_layer;
+        }
+        None
+    }
+
+    /// Nodes from which one of the nodes is reachable, including them.
+    fn get_ancestors(&self, nodes: &[usize]) -> HashSet<usize> {
+        let mut res: HashSet<usize> = nodes.iter().copied().collect();
+        let mut stack = nodes.to_vec();
+        while let Some(cur) = stack.pop() {
+            for other in &self.reverse_edges[cur] {
+                if res.insert(*other) {
+                    stack.push(*other);
+                }
+            }
+        }
+        res
+    }
+
+    /// Nodes reachable from the node by closure steps, including it.
+    fn get_closure(&self, node: usize) -> Vec<usize> {
+        let mut res = vec![node];
+        let mut seen = HashSet::from([node]);
+        let mut i = 0;
+        while i < res.len() {
+            for (other, symbol) in &self.edges[res[i]] {
+                if symbol.is_none() && seen.insert(*other) {
+                    res.push(*other);
+                }
+            }
+            i += 1;
+        }
+        res
+    }
+
+    fn matches(&self, goal: &Goal, item: &Item) -> bool {
+        if self.symbols.rule(item.rule) != goal.rule {
+            return false;
+        }
+        match (&goal.action, self.symbols.next_symbol(item)) {
+            (ConflictAction::Shift, Some(SymbolId::Terminal(t))) => {
+                goal.lookahead == Some(t as u32)
+            }
+            (ConflictAction::Reduce, None) => goal.lookahead.is_none_or(|x| x == item.lookup),
+            _ => false,
+        }
+    }
+
+    /// Shortest path from a start node to the node.
+    fn get_path(&self, node: usize) -> Path {
+        let mut res = Vec::new();
+        let mut cur = node;
+        loop {
+            match self.parents[cur] {
+                Some((prev, symbol)) => {
+                    res.push((self.nodes[cur].1, symbol));
+                    cur = prev;
+                }
+                None => {
+                    res.push((self.nodes[cur].1, None));
+                    break;
+                }
+            }
+        }
+        res.reverse();
+        res
+    }
+
+    fn get_joint_paths(
+        &self,
+        configs: &[Vec<usize>],
+        parents: &[Option<(usize, SymbolId, Vec<usize>)>],
+        last: usize,
+        found: &[usize],
+    ) -> Vec<Path> {
+        let mut steps = Vec::new();
+        let mut cur = last;
+        while let Some((prev, symbol, nodes)) = &parents[cur] {
+            steps.push((cur, *symbol, nodes));
+            cur = *prev;
+        }
+        steps.reverse();
+        (0..found.len())
+            .map(|i| {
+                let mut from = configs[cur][i];
+                let mut path = vec![(self.nodes[from].1, None)];
+                for (next, symbol, nodes) in &steps {
+                    path.extend(self.get_closure_path(from, nodes[i]));
+                    from = configs[*next][i];
+                    path.push((self.nodes[from].1, Some(*symbol)));
+                }
+                path.extend(self.get_closure_path(from, found[i]));
+                path
+            })
+            .collect()
+    }
+
+    /// Closure steps leading from one node to the other, without the first node.
+    fn get_closure_path(&self, from: usize, to: usize) -> Path {
+        let mut parents: HashMap<usize, usize> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(cur) = queue.pop_front() {
+            if cur == to {
+                break;
+            }
+            for (other, symbol) in &self.edges[cur] {
+                if symbol.is_none() && *other != from && !parents.contains_key(other) {
+                    parents.insert(*other, cur);
+                    queue.push_back(*other);
+                }
+            }
+        }
+        let mut res = Vec::new();
+        let mut cur = to;
+        while cur != from {
+            res.push((self.nodes[cur].1, None));
+            cur = parents[&cur];
+        }
+        res.reverse();
+        res
+    }
+
+    fn to_counterexample(&self, goal: &Goal, path: &Path) -> Counterexample {
+        let mut stack: Vec<Item> = Vec::new();
+        let mut prefix = Vec::new();
+        for (item, symbol) in path {
+            match symbol {
+                Some(symbol) => {
+                    *stack.last_mut().expect("shift before start item") = *item;
+                    prefix.push(self.symbols.term(*symbol));
+                }
+                None => stack.push(*item),
+            }
+        }
+        let item = stack.pop().expect("empty path");
+        let parents = stack
+            .iter()
+            .map(|x| self.symbols.resolve(x))
+            .filter(|x| x.rule.left.0 != GRAMMAR_AXIOM_NAME)
+            .collect();
+        let lookahead = match goal.action {
+            ConflictAction::Shift => goal.lookahead,
+            ConflictAction::Reduce => Some(item.lookup),
+        };
+        let lookahead = self
+            .symbols
+            .terminal(lookahead.expect("no lookahead"))
+            .clone();
+        Counterexample {
+            action: goal.action.clone(),
+            input: expand(&prefix, &self.yields),
+            prefix,
+            parents,
+            item: self.symbols.resolve(&item),
+            lookahead,
+        }
+    }
+}
+
+/// Breadth-first search from the first `starts` nodes by the number of shifts, and
+/// by the number of closure steps after the last shift.
+fn get_shortest_paths(
+    edges: &[Vec<(usize, Option<SymbolId>)>],
+    starts: usize,
+) -> (Vec<usize>, Vec<Parent>) {
+    let mut distances = vec![usize::MAX; edges.len()];
+    let mut parents = vec![None; edges.len()];
+    let mut layer: Vec<usize> = (0..starts).collect();
+    for start in &layer {
+        distances[*start] = 0;
+    }
+    let mut distance = 0;
+    while !layer.is_empty() {
+        let mut queue = VecDeque::from(layer);
+        let mut shifts = Vec::new();
+        while let Some(cur) = queue.pop_front() {
+            for (other, symbol) in &edges[cur] {
+                if symbol.is_some() {
+                    shifts.push((cur, *other, *symbol));
+                } else if distances[*other] == usize::MAX {
+                    distances[*other] = distance;
+                    parents[*other] = Some((cur, None));
+                    queue.push_back(*other);
+                }
+            }
+        }
+        distance += 1;
+        layer = Vec::new();
+        for (cur, other, symbol) in shifts {
+            if distances[other] == usize::MAX {
+                distances[other] = distance;
+                parents[other] = Some((cur, symbol));
+                layer.push(other);
+            }
+        }
+    }
+    (distances, parents)
+}
+
+/// All ways to pick one pair of each set.
+fn get_product(sets: &[Vec<(usize, usize)>]) -> Vec<Vec<(usize, usize)>> {
+    let mut res = vec![Vec::new()];
+    for set in sets {
+        res = res
+            .iter()
+            .flat_map(|pairs| {
+                set.iter().map(|x| {
+                    let mut pairs = pairs.clone();
+                    pairs.push(*x);
+                    pairs
+                })
+            })
+            .collect();
+    }
+    res
+}
+
+impl Counterexample {
+    /// Prints the rules of the parents with the item nested in them,
+    /// e.g. `[S -> a [S -> • a S a] a]` or `[S -> a [S -> •] a]`.
+    fn derivation_to_string(&self) -> String {
+        let mut res = item_to_string(&self.item);
+        for parent in self.parents.iter().rev() {
+            let position = parent.position as usize;
+            let mut outer = format!("[{} ->", parent.rule.left.0);
+            for term in &parent.rule.right[..position] {
+                outer += format!(" {}", term).as_ref();
+            }
+            outer += format!(" {}", res).as_ref();
+            for term in &parent.rule.right[position + 1..] {
+                outer += format!(" {}", term).as_ref();
+            }
+            res = outer + "]";
+        }
+        res
+    }
+}
+
+fn item_to_string(item: &LR1Item) -> String {
+    let mut res = format!("[{} ->", item.rule.left.0);
+    for (i, term) in item.rule.right.iter().enumerate() {
+        if i == item.position as usize {
+            res += " •";
+        }
+        res += format!(" {}", term).as_ref();
+    }
+    if item.is_finish() {
+        res += " •";
+    }
+    res + "]"
+}
+
+impl Display for Counterexample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match self.action {
+            ConflictAction::Shift => "shift ",
+            ConflictAction::Reduce => "reduce",
+        };
+        let mut input = String::new();
+        for t in &self.input {
+            input += format!("{} ", t.0).as_ref();
+        }
+        input += "•";
+        match &self.lookahead {
+            TerminalOrFinish::Terminal(t) => input += format!(" {}", t.0).as_ref(),
+            TerminalOrFinish::Finish => input += " EOF",
+        }
+        write!(
+            f,
+            "{}: {}\n    derivation: {}",
+            action,
+            input,
+            self.derivation_to_string()
+        )
+    }
+}
+
+/// `actions` is the number of actions of the conflict.
+pub fn counterexamples_to_string(examples: &[Counterexample], actions: usize) -> String {
+    let mut res = String::from("Counterexamples:");
+    for example in examples {
+        res += format!("\n  {}", example).as_ref();
+    }
+    let is_same_input = examples
+        .windows(2)
+        .all(|x| x[0].input == x[1].input && x[0].lookahead == x[1].lookahead);
+    if !is_same_input {
+        res += "\n  Note: the inputs differ, so the conflict is likely caused by merging states with equal LR(0) cores";
+    }
+    if examples.len() < actions {
+        res += "\n  Note: some actions are reachable only through nonterminals that derive no string of terminals, they have no counterexamples";
+    }
+    res
+}
+
+fn get_shortest_yields(symbols: &SymbolTable) -> HashMap<Nonterminal, Vec<Terminal>> {
+    let rules = symbols.rules();
+    let mut yields: HashMap<Nonterminal, Vec<Terminal>> = HashMap::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for rule in rules {
+            let mut res = Vec::new();
+            let mut is_productive = true;
+            for term in &rule.right {
+                match term {
+                    Term::Terminal(t) => res.push(t.clone()),
+                    Term::Nonterminal(nterm) => match yields.get(nterm) {
+                        Some(y) => res.extend(y.iter().cloned()),
+                        None => {
+                            is_productive = false;
+                            break;
+                        }
+                    },
+                }
+            }
+            let is_shorter = match yields.get(&rule.left) {
+                Some(y) => res.len() < y.len(),
+                None => true,
+            };
+            if is_productive && is_shorter {
+                yields.insert(rule.left.clone(), res);
+                changed = true;
+            }
+        }
+    }
+    yields
+}
+
+/// Only productive nonterminals are shifted by the search, so all of them have yields.
+fn expand(prefix: &[Term], yields: &HashMap<Nonterminal, Vec<Terminal>>) -> Vec<Terminal> {
+    let mut res = Vec::new();
+    for term in prefix {
+        match term {
+            Term::Terminal(t) => res.push(t.clone()),
+            Term::Nonterminal(nterm) => res.extend(yields[nterm].iter().cloned()),
+        }
+    }
+    res
+}
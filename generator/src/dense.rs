@@ -0,0 +1,220 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::parser::*;
+use crate::with_runtime;
+
+impl ParseTables {
+    /// Like `to_rust_source_with`, but `get_parse_tables()` returns `DenseParseTables`
+    /// in static arrays instead of building `HashMap`s.
+    pub fn to_dense_rust_source_with(&self, extra: &[String]) -> String {
+        with_runtime(&self.to_dense_rust_function(), extra)
+    }
+
+    fn to_dense_rust_function(&self) -> String {
+        let tables = self.to_dense_tables();
+        let rules: Vec<String> = tables
+            .rules
+            .iter()
+            .map(|(left, right)| {
+                let right: Vec<String> = right
+                    .iter()
+                    .map(|x| match x {
+                        SymbolId::Terminal(t) => format!("SymbolId::Terminal({})", t),
+                        SymbolId::Nonterminal(n) => format!("SymbolId::Nonterminal({})", n),
+                    })
+                    .collect();
+                format!(
+                    "DenseRule {{ left: {}, right: &[{}] }},\n",
+                    left,
+                    right.join(", ")
+                )
+            })
+            .collect();
+        let entries: Vec<String> = tables
+            .entries
+            .iter()
+            .map(|(x, state)| format!("({:?}, {})", x, state))
+            .collect();
+        let terminals: Vec<String> = tables
+            .terminals
+            .iter()
+            .map(|x| format!("{:?}", x))
+            .collect();
+        let nonterminals: Vec<String> = tables
+            .nonterminals
+            .iter()
+            .map(|x| format!("{:?}", x))
+            .collect();
+        format!(
+            r#"
+        static PARSE_TABLES: DenseParseTables = DenseParseTables {{
+            start: {},
+            entries: &[{}],
+            terminals: &[{}],
+            nonterminals: &[{}],
+            rules: &[
+                {}
+            ],
+            action: {},
+            goto: {},
+        }};
+
+        pub fn get_parse_tables() -> &'static DenseParseTables {{
+            &PARSE_TABLES
+        }}
+        "#,
+            tables.start,
+            entries.join(", "),
+            terminals.join(", "),
+            nonterminals.join(", "),
+            rules.concat(),
+            displaced_table_to_rust(&tables.action),
+            displaced_table_to_rust(&tables.goto),
+        )
+    }
+
+    /// Numbers the symbols and compresses the tables, the fields are the ones of
+    /// `DenseParseTables` in owned vectors.
+    pub(crate) fn to_dense_tables(&self) -> DenseTables {
+        let terminals: Vec<&Terminal> = self
+            .action
+            .keys()
+            .filter_map(|(_, x)| match x {
+                TerminalOrFinish::Terminal(t) => Some(t),
+                TerminalOrFinish::Finish => None,
+            })
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let rules: Vec<&Rule> = self
+            .action
+            .values()
+            .filter_map(|x| match x {
+                LR1Action::Reduce(rule) => Some(rule),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let nonterminals: Vec<&Nonterminal> = self
+            .goto
+            .keys()
+            .map(|(_, x)| x)
+            .chain(rules.iter().map(|x| &x.left))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let terminal_id = |t: &TerminalOrFinish| match t {
+            TerminalOrFinish::Terminal(t) => terminals.binary_search(&t).unwrap() + 1,
+            TerminalOrFinish::Finish => 0,
+        };
+        let nonterminal_id = |n: &Nonterminal| nonterminals.binary_search(&n).unwrap();
+        let states = self
+            .action
+            .keys()
+            .map(|(x, _)| *x)
+            .chain(self.goto.keys().map(|(x, _)| *x))
+            .chain([self.start])
+            .max()
+            .unwrap() as usize
+            + 1;
+        let mut action_rows = vec![Vec::new(); states];
+        for ((state, term), action) in &self.action {
+            let value = match action {
+                LR1Action::Shift(next) => 3 * next,
+                LR1Action::Reduce(rule) => 3 * rules.binary_search(&rule).unwrap() as i32 + 1,
+                LR1Action::Accept => 2,
+            };
+            action_rows[*state as usize].push((terminal_id(term), value));
+        }
+        let mut goto_rows = vec![Vec::new(); states];
+        for ((state, nterm), next) in &self.goto {
+            goto_rows[*state as usize].push((nonterminal_id(nterm), *next));
+        }
+        let dense_rules = rules
+            .iter()
+            .map(|rule| {
+                let right = rule
+                    .right
+                    .iter()
+                    .map(|x| match x {
+                        Term::Terminal(t) => {
+                            SymbolId::Terminal(terminal_id(&TerminalOrFinish::Terminal(t.clone())))
+                        }
+                        Term::Nonterminal(n) => SymbolId::Nonterminal(nonterminal_id(n)),
+                    })
+                    .collect();
+                (nonterminal_id(&rule.left), right)
+            })
+            .collect();
+        DenseTables {
+            start: self.start as usize,
+            entries: self
+                .entries
+                .iter()
+                .map(|(x, state)| (x.0.clone(), *state as usize))
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .collect(),
+            terminals: terminals.iter().map(|x| x.0.clone()).collect(),
+            nonterminals: nonterminals.iter().map(|x| x.0.clone()).collect(),
+            rules: dense_rules,
+            action: displace(&action_rows),
+            goto: displace(&goto_rows),
+        }
+    }
+}
+
+/// `DenseParseTables` before they are written as static arrays.
+pub(crate) struct DenseTables {
+    pub start: usize,
+    pub entries: Vec<(String, usize)>,
+    pub terminals: Vec<String>,
+    pub nonterminals: Vec<String>,
+    /// left side and right side of every rule
+    pub rules: Vec<(usize, Vec<SymbolId>)>,
+    pub action: Displaced,
+    pub goto: Displaced,
+}
+
+/// Result of the row displacement: `base`, `check` and `value` arrays.
+pub(crate) type Displaced = (Vec<usize>, Vec<i32>, Vec<i32>);
+
+/// Overlaps sparse rows `(column, value)` in one array, every row is placed at the first
+/// offset where its entries fall into free cells. Denser rows are placed first.
+pub(crate) fn displace(rows: &[Vec<(usize, i32)>]) -> Displaced {
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    order.sort_by_key(|x| std::cmp::Reverse(rows[*x].len()));
+    let mut base = vec![0; rows.len()];
+    let mut check: Vec<i32> = Vec::new();
+    let mut value = Vec::new();
+    for row in order {
+        if rows[row].is_empty() {
+            continue;
+        }
+        let is_free = |offset: usize| {
+            rows[row]
+                .iter()
+                .all(|(column, _)| check.get(offset + column).is_none_or(|x| *x == -1))
+        };
+        let offset = (0..).find(|x| is_free(*x)).unwrap();
+        for (column, v) in &rows[row] {
+            let index = offset + column;
+            if index >= check.len() {
+                check.resize(index + 1, -1);
+                value.resize(index + 1, 0);
+            }
+            check[index] = row as i32;
+            value[index] = *v;
+        }
+        base[row] = offset;
+    }
+    (base, check, value)
+}
+
+fn displaced_table_to_rust((base, check, value): &Displaced) -> String {
+    format!(
+        "DisplacedTable {{ base: &{:?}, check: &{:?}, value: &{:?} }}",
+        base, check, value
+    )
+}
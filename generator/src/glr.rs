@@ -0,0 +1,61 @@
+use crate::parser::*;
+use crate::{
+    action_to_literal, goto_to_literal, terminal_or_finish_to_literal, with_runtime,
+    DetermenisticLR1Automaton, GeneratorError, Grammar, ParseTablesType, TablesBuilder,
+};
+
+impl GLRParseTables {
+    pub fn from_string(
+        input: &str,
+        tables_type: ParseTablesType,
+    ) -> Result<GLRParseTables, GeneratorError> {
+        let grammar = Grammar::from_string(input)?;
+        Ok(GLRParseTables::from_grammar(&grammar, tables_type))
+    }
+
+    /// Tables of the type where unresolved conflicts keep all their actions.
+    pub fn from_grammar(grammar: &Grammar, tables_type: ParseTablesType) -> GLRParseTables {
+        let automaton = DetermenisticLR1Automaton::from_grammar_for(grammar, tables_type);
+        TablesBuilder::new(&automaton, ParseTablesType::LR1).build_glr()
+    }
+
+    /// Like `ParseTables::to_rust_source_with`, but `get_parse_tables()` returns
+    /// `GLRParseTables`.
+    pub fn to_rust_source_with(&self, extra: &[String]) -> String {
+        with_runtime(&self.to_rust_function(), extra)
+    }
+
+    fn to_rust_function(&self) -> String {
+        let mut action_entries = String::new();
+        for ((state, term), actions) in &self.action {
+            let actions: Vec<String> = actions.iter().map(action_to_literal).collect();
+            let entry = format!(
+                "(({}, {}), vec![{}]),\n",
+                state,
+                terminal_or_finish_to_literal(term),
+                actions.join(", ")
+            );
+            action_entries += entry.as_ref();
+        }
+        format!(
+            r#"
+        pub fn get_parse_tables() -> GLRParseTables {{
+            let action = [
+                {}
+            ].into_iter().collect();
+            let goto = [
+                {}
+            ].into_iter().collect();
+            GLRParseTables {{
+                start: {},
+                action,
+                goto,
+            }}
+        }}
+        "#,
+            action_entries,
+            goto_to_literal(&self.goto),
+            self.start
+        )
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::parser::*;
+use crate::symbols::{Item, SymbolTable};
+use crate::{
+    calculate_first, calculate_follow, DetermenisticLR1Automaton, Grammar, LR0Item,
+    NonDeterministicLR1Automaton,
+};
+
+/// Lookahead of the closure items whose lookaheads are propagated from the kernel item.
+const PROPAGATED: u32 = u32::MAX;
+
+/// Kernel item of the LR(0) automaton with the index of its state.
+type KernelItem = (usize, LR0Item);
+
+impl DetermenisticLR1Automaton {
+    /// Builds the LALR(1) automaton without canonical LR(1) states: lookaheads of kernel
+    /// items of the LR(0) automaton are either generated spontaneously or propagated from
+    /// other kernel items. States have the same items as the canonical ones merged by cores.
+    pub fn lalr_from_grammar(grammar: &Grammar) -> DetermenisticLR1Automaton {
+        let symbols = SymbolTable::from_grammar(grammar);
+        let first = NonDeterministicLR1Automaton::get_first_ids(&symbols);
+        let closure = |items: BTreeSet<Item>| get_closure(&symbols, &first, items);
+        let start_items = NonDeterministicLR1Automaton::get_start_items(&symbols);
+        let (kernels, gotos) = get_lr0_automaton(&symbols, &start_items);
+
+        let mut lookaheads: HashMap<KernelItem, BTreeSet<u32>> = HashMap::new();
+        // lookaheads generated by a kernel item only if it has lookaheads itself, items of
+        // unproductive nonterminals have none, like in the canonical automaton
+        let mut spontaneous: HashMap<KernelItem, Vec<(KernelItem, u32)>> = HashMap::new();
+        let mut propagation: HashMap<KernelItem, Vec<KernelItem>> = HashMap::new();
+        for (state, item) in start_items.iter().enumerate() {
+            lookaheads.insert((state, LR0Item::from_item(item)), BTreeSet::from([0]));
+        }
+        for (state, kernel) in kernels.iter().enumerate() {
+            for item in kernel {
+                let items = closure(BTreeSet::from([Item {
+                    rule: item.rule,
+                    position: item.position,
+                    lookup: PROPAGATED,
+                }]));
+                for other in items {
+                    let Some(symbol) = symbols.next_symbol(&other) else {
+                        continue;
+                    };
+                    let target = (
+                        gotos[state][&symbol],
+                        LR0Item {
+                            rule: other.rule,
+                            position: other.position + 1,
+                        },
+                    );
+                    if other.lookup == PROPAGATED {
+                        propagation.entry((state, *item)).or_default().push(target);
+                    } else {
+                        let generated = spontaneous.entry((state, *item)).or_default();
+                        generated.push((target, other.lookup));
+                    }
+                }
+            }
+        }
+        let mut stack: Vec<KernelItem> = lookaheads.keys().copied().collect();
+        while let Some(cur) = stack.pop() {
+            let cur_lookaheads = lookaheads[&cur].clone();
+            let generated = spontaneous.get(&cur).into_iter().flatten();
+            let generated = generated.map(|(target, lookup)| (target, BTreeSet::from([*lookup])));
+            let propagated = propagation.get(&cur).into_iter().flatten();
+            let propagated = propagated.map(|target| (target, cur_lookaheads.clone()));
+            for (target, new) in generated.chain(propagated) {
+                let target_lookaheads = lookaheads.entry(*target).or_default();
+                let size = target_lookaheads.len();
+                target_lookaheads.extend(new);
+                if target_lookaheads.len() != size {
+                    stack.push(*target);
+                }
+            }
+        }
+
+        let states: Vec<BTreeSet<Item>> = kernels
+            .iter()
+            .enumerate()
+            .map(|(state, kernel)| {
+                // items of unproductive nonterminals may get no lookaheads
+                let items = kernel.iter().flat_map(|item| {
+                    let lookups = lookaheads.get(&(state, *item)).into_iter().flatten();
+                    lookups.map(|lookup| Item {
+                        rule: item.rule,
+                        position: item.position,
+                        lookup: *lookup,
+                    })
+                });
+                closure(items.collect())
+            })
+            .collect();
+        let entries = start_items.len();
+        DetermenisticLR1Automaton::from_lr0_states(symbols, grammar, states, entries, &gotos)
+    }
+
+    /// Builds the LR(0) automaton where items have FOLLOW sets of the left sides of their
+    /// rules as lookaheads, so tables reduce like SLR(1) ones.
+    pub fn slr_from_grammar(grammar: &Grammar) -> DetermenisticLR1Automaton {
+        let symbols = SymbolTable::from_grammar(grammar);
+        let follow = calculate_follow(grammar, &calculate_first(grammar));
+        let lookaheads = (0..symbols.nonterminal_count() as u32)
+            .map(|id| {
+                follow[symbols.nonterminal(id)]
+                    .iter()
+                    .map(|x| match x {
+                        TerminalOrFinish::Terminal(t) => symbols.get_terminal_id(t).unwrap(),
+                        TerminalOrFinish::Finish => 0,
+                    })
+                    .collect()
+            })
+            .collect();
+        DetermenisticLR1Automaton::with_lookaheads(symbols, grammar, lookaheads)
+    }
+
+    /// Builds the LR(0) automaton where items have every terminal as lookaheads.
+    pub fn lr0_from_grammar(grammar: &Grammar) -> DetermenisticLR1Automaton {
+        let symbols = SymbolTable::from_grammar(grammar);
+        let all: Vec<u32> = (0..symbols.terminal_count() as u32).collect();
+        let lookaheads = vec![all; symbols.nonterminal_count()];
+        DetermenisticLR1Automaton::with_lookaheads(symbols, grammar, lookaheads)
+    }
+
+    /// LR(0) automaton whose items have lookaheads of the left sides of their rules.
+    fn with_lookaheads(
+        symbols: SymbolTable,
+        grammar: &Grammar,
+        lookaheads: Vec<Vec<u32>>,
+    ) -> DetermenisticLR1Automaton {
+        let start_items = NonDeterministicLR1Automaton::get_start_items(&symbols);
+        let (kernels, gotos) = get_lr0_automaton(&symbols, &start_items);
+        let states = kernels
+            .iter()
+            .map(|kernel| {
+                let mut items = BTreeSet::new();
+                for item in get_lr0_closure(&symbols, kernel) {
+                    let left = symbols.get_nonterminal_id(&symbols.rule(item.rule).left);
+                    let lookaheads = &lookaheads[left.unwrap() as usize];
+                    items.extend(lookaheads.iter().map(|lookup| Item {
+                        rule: item.rule,
+                        position: item.position,
+                        lookup: *lookup,
+                    }));
+                }
+                items
+            })
+            .collect();
+        let entries = start_items.len();
+        DetermenisticLR1Automaton::from_lr0_states(symbols, grammar, states, entries, &gotos)
+    }
+
+    /// The first `entries` states are the start states of the entries.
+    fn from_lr0_states(
+        symbols: SymbolTable,
+        grammar: &Grammar,
+        states: Vec<BTreeSet<Item>>,
+        entries: usize,
+        gotos: &[HashMap<SymbolId, usize>],
+    ) -> DetermenisticLR1Automaton {
+        // items without lookaheads are dropped like in the canonical automaton, so
+        // transitions only on their symbols and states left empty are dropped too
+        let mut edges = HashMap::new();
+        for (state, adjacent) in gotos.iter().enumerate() {
+            if states[state].is_empty() {
+                continue;
+            }
+            let adjacent = adjacent
+                .iter()
+                .filter(|(symbol, _)| {
+                    let next = |x: &Item| symbols.next_symbol(x) == Some(**symbol);
+                    states[state].iter().any(next)
+                })
+                .map(|(symbol, other)| (states[*other].clone(), *symbol))
+                .collect();
+            edges.insert(states[state].clone(), adjacent);
+        }
+        DetermenisticLR1Automaton {
+            symbols,
+            edges,
+            start: states[0].clone(),
+            entries: states[1..entries].to_vec(),
+            precedence: grammar.precedence.clone(),
+        }
+    }
+}
+
+/// Returns kernels of the states of the LR(0) automaton and their transitions,
+/// the start states of the items come first in the same order.
+fn get_lr0_automaton(
+    symbols: &SymbolTable,
+    start_items: &[Item],
+) -> (Vec<BTreeSet<LR0Item>>, Vec<HashMap<SymbolId, usize>>) {
+    let mut kernels: Vec<BTreeSet<LR0Item>> = start_items
+        .iter()
+        .map(|x| BTreeSet::from([LR0Item::from_item(x)]))
+        .collect();
+    let mut ids: HashMap<BTreeSet<LR0Item>, usize> = kernels
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(i, x)| (x, i))
+        .collect();
+    let mut gotos = Vec::new();
+    while gotos.len() < kernels.len() {
+        let mut next: HashMap<SymbolId, BTreeSet<LR0Item>> = HashMap::new();
+        for item in get_lr0_closure(symbols, &kernels[gotos.len()]) {
+            let right = symbols.right(item.rule);
+            if let Some(symbol) = right.get(item.position as usize) {
+                next.entry(*symbol).or_default().insert(LR0Item {
+                    rule: item.rule,
+                    position: item.position + 1,
+                });
+            }
+        }
+        let mut adjacent = HashMap::new();
+        for (symbol, kernel) in next {
+            let id = match ids.get(&kernel) {
+                Some(id) => *id,
+                None => {
+                    ids.insert(kernel.clone(), kernels.len());
+                    kernels.push(kernel);
+                    kernels.len() - 1
+                }
+            };
+            adjacent.insert(symbol, id);
+        }
+        gotos.push(adjacent);
+    }
+    (kernels, gotos)
+}
+
+fn get_lr0_closure(symbols: &SymbolTable, kernel: &BTreeSet<LR0Item>) -> BTreeSet<LR0Item> {
+    let mut res = kernel.clone();
+    let mut stack: Vec<LR0Item> = kernel.iter().copied().collect();
+    while let Some(cur) = stack.pop() {
+        let right = symbols.right(cur.rule);
+        if let Some(SymbolId::Nonterminal(n)) = right.get(cur.position as usize) {
+            for rule in symbols.rules_of(*n as u32) {
+                let item = LR0Item {
+                    rule: *rule,
+                    position: 0,
+                };
+                if res.insert(item) {
+                    stack.push(item);
+                }
+            }
+        }
+    }
+    res
+}
+
+pub(crate) fn get_closure(
+    symbols: &SymbolTable,
+    first: &[(Vec<u32>, bool)],
+    items: BTreeSet<Item>,
+) -> BTreeSet<Item> {
+    let mut stack: Vec<Item> = items.iter().copied().collect();
+    let mut res = items;
+    while let Some(cur) = stack.pop() {
+        for (other, symbol) in NonDeterministicLR1Automaton::get_adjacent(symbols, first, &cur) {
+            if symbol.is_none() && res.insert(other) {
+                stack.push(other);
+            }
+        }
+    }
+    res
+}
@@ -0,0 +1,241 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use crate::parser::*;
+use crate::regex::Regex;
+use crate::{GeneratorError, Grammar};
+
+/// NFA built by Thompson's construction, `accept` of a state is the priority
+/// of the lexer rule it finishes.
+#[derive(Default)]
+struct Nfa {
+    epsilon: Vec<Vec<usize>>,
+    edges: Vec<Vec<(char, char, usize)>>,
+    accept: Vec<Option<usize>>,
+}
+
+impl Nfa {
+    fn add_state(&mut self) -> usize {
+        self.epsilon.push(Vec::new());
+        self.edges.push(Vec::new());
+        self.accept.push(None);
+        self.epsilon.len() - 1
+    }
+
+    /// Adds states matching the regex, returns the start and the finish state.
+    fn add_regex(&mut self, regex: &Regex) -> (usize, usize) {
+        let start = self.add_state();
+        let finish = self.add_state();
+        match regex {
+            Regex::Empty => self.epsilon[start].push(finish),
+            Regex::Class(ranges) => {
+                for (from, to) in ranges {
+                    self.edges[start].push((*from, *to, finish));
+                }
+            }
+            Regex::Concat(v) => {
+                let mut cur = start;
+                for x in v {
+                    let (begin, end) = self.add_regex(x);
+                    self.epsilon[cur].push(begin);
+                    cur = end;
+                }
+                self.epsilon[cur].push(finish);
+            }
+            Regex::Alternation(v) => {
+                for x in v {
+                    let (begin, end) = self.add_regex(x);
+                    self.epsilon[start].push(begin);
+                    self.epsilon[end].push(finish);
+                }
+            }
+            Regex::Star(x) | Regex::Plus(x) | Regex::Optional(x) => {
+                let (begin, end) = self.add_regex(x);
+                self.epsilon[start].push(begin);
+                self.epsilon[end].push(finish);
+                if !matches!(regex, Regex::Plus(_)) {
+                    self.epsilon[start].push(finish);
+                }
+                if !matches!(regex, Regex::Optional(_)) {
+                    self.epsilon[end].push(begin);
+                }
+            }
+        }
+        (start, finish)
+    }
+
+    fn get_epsilon_closure(&self, states: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        let mut res = BTreeSet::new();
+        let mut stack: Vec<usize> = states.into_iter().collect();
+        while let Some(cur) = stack.pop() {
+            if res.insert(cur) {
+                stack.extend(self.epsilon[cur].iter().copied());
+            }
+        }
+        res
+    }
+}
+
+impl LexerTables {
+    /// Builds a lexer for the token and skip declarations of the grammar. Terminals
+    /// without a declaration (except `error`) are matched literally and win over declared
+    /// tokens on matches of equal length, otherwise the earlier declaration wins.
+    pub fn from_grammar(grammar: &Grammar) -> Result<LexerTables, GeneratorError> {
+        let mut declared = HashMap::new();
+        for declaration in &grammar.tokens {
+            if let Some(t) = &declaration.terminal {
+                if let Some(previous) = declared.insert(t, &declaration.fragment) {
+                    return Err(GeneratorError::DuplicateToken {
+                        terminal: t.clone(),
+                        fragment: Some(declaration.fragment.clone()),
+                        previous: Some(previous.clone()),
+                    });
+                }
+            }
+        }
+        let mut actions = Vec::new();
+        let mut regexes = Vec::new();
+        for rule in &grammar.rules {
+            for term in &rule.right {
+                if let Term::Terminal(t) = term {
+                    let action = LexerAction::Token(t.clone());
+                    let is_error = t.0 == ERROR_TERMINAL_NAME;
+                    if !is_error && !declared.contains_key(t) && !actions.contains(&action) {
+                        actions.push(action);
+                        regexes.push(Regex::literal(&t.0));
+                    }
+                }
+            }
+        }
+        for declaration in &grammar.tokens {
+            let invalid_regex = |message| GeneratorError::InvalidRegex {
+                regex: declaration.regex.clone(),
+                message,
+                fragment: Some(declaration.fragment.clone()),
+            };
+            let regex = Regex::parse(&declaration.regex).map_err(invalid_regex)?;
+            if regex.is_nullable() {
+                return Err(invalid_regex(String::from("matches the empty string")));
+            }
+            actions.push(match &declaration.terminal {
+                Some(t) => LexerAction::Token(t.clone()),
+                None => LexerAction::Skip,
+            });
+            regexes.push(regex);
+        }
+        let mut nfa = Nfa::default();
+        let start = nfa.add_state();
+        for (priority, regex) in regexes.iter().enumerate() {
+            let (begin, end) = nfa.add_regex(regex);
+            nfa.epsilon[start].push(begin);
+            nfa.accept[end] = Some(priority);
+        }
+        Ok(determinize(&nfa, start, &actions))
+    }
+
+    pub fn to_rust_function(&self) -> String {
+        let mut transitions = String::new();
+        for edges in &self.transitions {
+            let edges: Vec<String> = edges
+                .iter()
+                .map(|(from, to, next)| format!("({:?}, {:?}, {})", from, to, next))
+                .collect();
+            transitions += format!("vec![{}],\n", edges.join(", ")).as_ref();
+        }
+        let mut accept = String::new();
+        for action in &self.accept {
+            let action = match action {
+                Some(LexerAction::Token(t)) => {
+                    format!(
+                        "Some(LexerAction::Token(Terminal(String::from({:?}))))",
+                        t.0
+                    )
+                }
+                Some(LexerAction::Skip) => String::from("Some(LexerAction::Skip)"),
+                None => String::from("None"),
+            };
+            accept += format!("{},\n", action).as_ref();
+        }
+        format!(
+            r#"
+        pub fn get_lexer_tables() -> LexerTables {{
+            LexerTables {{
+                start: {},
+                transitions: vec![
+                    {}
+                ],
+                accept: vec![
+                    {}
+                ],
+            }}
+        }}
+        "#,
+            self.start, transitions, accept
+        )
+    }
+}
+
+/// Subset construction, transitions on characters of equal behaviour are merged into ranges.
+fn determinize(nfa: &Nfa, start: usize, actions: &[LexerAction]) -> LexerTables {
+    let start = nfa.get_epsilon_closure([start]);
+    let mut ids = HashMap::from([(start.clone(), 0)]);
+    let mut states = vec![start];
+    let mut queue = VecDeque::from([0]);
+    let mut tables = LexerTables {
+        start: 0,
+        transitions: vec![Vec::new()],
+        accept: vec![None],
+    };
+    while let Some(cur) = queue.pop_front() {
+        let edges: Vec<(char, char, usize)> = states[cur]
+            .iter()
+            .flat_map(|x| nfa.edges[*x].iter().copied())
+            .collect();
+        let mut bounds: Vec<u32> = edges
+            .iter()
+            .flat_map(|(from, to, _)| [*from as u32, *to as u32 + 1])
+            .collect();
+        bounds.sort();
+        bounds.dedup();
+        let mut transitions: Vec<(char, char, usize)> = Vec::new();
+        for window in bounds.windows(2) {
+            let (Some(from), Some(to)) = (char::from_u32(window[0]), char::from_u32(window[1] - 1))
+            else {
+                continue;
+            };
+            let targets: Vec<usize> = edges
+                .iter()
+                .filter(|(x, y, _)| *x <= from && to <= *y)
+                .map(|(_, _, next)| *next)
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+            let next = nfa.get_epsilon_closure(targets);
+            let next_id = match ids.get(&next) {
+                Some(id) => *id,
+                None => {
+                    let id = states.len();
+                    ids.insert(next.clone(), id);
+                    tables.transitions.push(Vec::new());
+                    tables.accept.push(
+                        next.iter()
+                            .filter_map(|x| nfa.accept[*x])
+                            .min()
+                            .map(|x| actions[x].clone()),
+                    );
+                    states.push(next);
+                    queue.push_back(id);
+                    id
+                }
+            };
+            match transitions.last_mut() {
+                Some(last) if last.2 == next_id && last.1 as u32 + 1 == from as u32 => {
+                    last.1 = to;
+                }
+                _ => transitions.push((from, to, next_id)),
+            }
+        }
+        tables.transitions[cur] = transitions;
+    }
+    tables
+}
@@ -1,23 +1,266 @@
+mod actions;
+mod ast;
+mod counterexample;
+mod dense;
+mod glr;
+mod lalr;
+mod lexgen;
+mod lint;
+mod ll1;
 pub mod parser;
+mod pgm;
+mod regex;
+mod symbols;
 #[cfg(test)]
 mod tests;
 
+pub use actions::Semantics;
+pub use counterexample::{ConflictAction, Counterexample};
+pub use lint::{Lint, LintLevel, LintWarning};
+
+use counterexample::CounterexampleSearch;
+use symbols::{Item, SymbolTable};
+
 use crate::parser::*;
 
-const GRAMMAR_AXIOM_NAME: &str = "ROOT";
+/// Name of the fake axiom. Like the names of generated helpers it contains `<`, so
+/// it can not clash with a nonterminal of a grammar.
+const GRAMMAR_AXIOM_NAME: &str = "<ROOT>";
+
+/// Keywords of grammars, all but `axiom` are terminals outside of their positions.
+const RESERVED_WORDS: [&str; 9] = [
+    "axiom",
+    "prec",
+    "token",
+    "skip",
+    "attribute",
+    "left",
+    "right",
+    "nonassoc",
+    "import",
+];
 
 use std::{
-    collections::{BTreeSet, HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
     fmt::Display,
+    fs,
     hash::Hash,
+    path::{Path, PathBuf},
 };
 
+/// Fragments point into the grammar source, they are `None` for grammars built in code.
 #[derive(Debug)]
 pub enum GeneratorError {
     ParseError(ParseError<TokenAttribute>),
-    UndeclaredNonterminal(Nonterminal),
-    ShiftReduceConflict,
-    ReduceReduceConflict,
+    /// Quoted terminal without the closing quote, the fragment is the opening quote.
+    UnterminatedLiteral(Fragment),
+    UndeclaredNonterminal(Nonterminal, Option<Fragment>),
+    /// Terminal of a `prec` annotation without a precedence level.
+    UndeclaredPrecedence {
+        terminal: Terminal,
+        fragment: Option<Fragment>,
+    },
+    ShiftReduceConflict(Box<Conflict>),
+    ReduceReduceConflict(Box<Conflict>),
+    Conflicts(Vec<GeneratorError>),
+    InvalidRegex {
+        regex: String,
+        message: String,
+        fragment: Option<Fragment>,
+    },
+    /// `previous` is the first declaration, maybe in another file.
+    DuplicateToken {
+        terminal: Terminal,
+        fragment: Option<Fragment>,
+        previous: Option<Fragment>,
+    },
+    /// Type of a nonterminal, precedence of a terminal or the attribute type declared
+    /// twice, `previous` is the first declaration.
+    DuplicateDefinition {
+        name: String,
+        fragment: Option<Fragment>,
+        previous: Option<Fragment>,
+    },
+    /// File of an `import` declaration that can not be read.
+    ImportError {
+        path: String,
+        message: String,
+        fragment: Option<Fragment>,
+    },
+    MissingAction(Rule, Option<Fragment>),
+    InvalidActionReference {
+        rule: Rule,
+        index: usize,
+        fragment: Option<Fragment>,
+    },
+    /// Rules of the nonterminal with the lookahead in their PREDICT sets.
+    LL1Conflict {
+        nonterminal: Nonterminal,
+        lookahead: TerminalOrFinish,
+        rules: Vec<Rule>,
+        fragment: Option<Fragment>,
+    },
+    /// Lints denied from the command line.
+    Lints(Vec<LintWarning>),
+}
+
+impl GeneratorError {
+    pub fn as_conflict(&self) -> Option<&Conflict> {
+        match self {
+            GeneratorError::ShiftReduceConflict(conflict)
+            | GeneratorError::ReduceReduceConflict(conflict) => Some(conflict),
+            _ => None,
+        }
+    }
+
+    fn as_conflict_mut(&mut self) -> Option<&mut Conflict> {
+        match self {
+            GeneratorError::ShiftReduceConflict(conflict)
+            | GeneratorError::ReduceReduceConflict(conflict) => Some(conflict),
+            _ => None,
+        }
+    }
+
+    /// Returns the fragment of the grammar source the error points to.
+    pub fn fragment(&self) -> Option<&Fragment> {
+        match self {
+            GeneratorError::ParseError(err) => Some(&err.token.attribute.fragment),
+            GeneratorError::UnterminatedLiteral(fragment) => Some(fragment),
+            GeneratorError::ShiftReduceConflict(conflict)
+            | GeneratorError::ReduceReduceConflict(conflict) => conflict.fragment.as_ref(),
+            GeneratorError::Conflicts(_) | GeneratorError::Lints(_) => None,
+            GeneratorError::UndeclaredNonterminal(_, fragment)
+            | GeneratorError::MissingAction(_, fragment)
+            | GeneratorError::UndeclaredPrecedence { fragment, .. }
+            | GeneratorError::InvalidRegex { fragment, .. }
+            | GeneratorError::DuplicateToken { fragment, .. }
+            | GeneratorError::DuplicateDefinition { fragment, .. }
+            | GeneratorError::ImportError { fragment, .. }
+            | GeneratorError::InvalidActionReference { fragment, .. }
+            | GeneratorError::LL1Conflict { fragment, .. } => fragment.as_ref(),
+        }
+    }
+
+    /// Formats the error as `file:line:column: message` with the fragment of the error
+    /// underlined in the grammar source.
+    pub fn render(&self, source: &str, file: &str) -> String {
+        self.render_sources(&[Source::new(file, source)])
+    }
+
+    /// Like `render` for a grammar read from several files.
+    pub fn render_sources(&self, sources: &[Source]) -> String {
+        if let GeneratorError::Conflicts(conflicts) = self {
+            let mut res = format!(
+                "Found {} conflicts while generating tables",
+                conflicts.len()
+            );
+            for conflict in conflicts {
+                res += format!("\n\n{}", conflict.render_sources(sources)).as_ref();
+            }
+            return res;
+        }
+        if let GeneratorError::Lints(lints) = self {
+            let lints: Vec<String> = lints.iter().map(|x| x.render_sources(sources)).collect();
+            return lints.join("\n");
+        }
+        let res = render_message(&self.to_string(), self.fragment(), sources);
+        match self {
+            GeneratorError::DuplicateToken {
+                previous: Some(previous),
+                ..
+            }
+            | GeneratorError::DuplicateDefinition {
+                previous: Some(previous),
+                ..
+            } => {
+                let note = render_message("previous definition is here", Some(previous), sources);
+                format!("{}\n{}", res, note)
+            }
+            _ => res,
+        }
+    }
+}
+
+/// Formats the message as `file:line:column: message`, the first line of the message is
+/// followed by the line of the grammar source with the fragment underlined.
+fn render_message(message: &str, fragment: Option<&Fragment>, sources: &[Source]) -> String {
+    let Some(fragment) = fragment else {
+        let file = sources.first().map_or("", |x| x.name.as_str());
+        return format!("{}: {}", file, message);
+    };
+    let Source {
+        name: file,
+        text: source,
+    } = &sources[fragment.file];
+    let begin = &fragment.begin;
+    let (first, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let mut res = format!("{}:{}:{}: {}", file, begin.line, begin.column, first);
+    let Some(line) = source.lines().nth(begin.line as usize - 1) else {
+        return format!("{}\n{}", res, rest).trim_end().to_string();
+    };
+    let prefix: String = line
+        .chars()
+        .take(begin.column as usize - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let width = if fragment.end.line == begin.line {
+        fragment.end.column - begin.column
+    } else {
+        line.chars().count() as i32 - begin.column + 1
+    };
+    let number = begin.line.to_string();
+    let margin = " ".repeat(number.len());
+    res += format!("\n{} |\n{} | {}", margin, number, line).as_ref();
+    res += format!(
+        "\n{} | {}{}",
+        margin,
+        prefix,
+        "^".repeat(width.max(1) as usize)
+    )
+    .as_ref();
+    if !rest.is_empty() {
+        res += format!("\n{}", rest).as_ref();
+    }
+    res
+}
+
+#[derive(Debug)]
+pub struct Conflict {
+    pub state: i32,
+    pub lookahead: TerminalOrFinish,
+    pub shift: Vec<Rule>,
+    pub reduce: Vec<Rule>,
+    pub items: BTreeSet<LR1Item>,
+    pub counterexamples: Vec<Counterexample>,
+    /// fragment of the first reduced rule
+    pub fragment: Option<Fragment>,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lookahead = match &self.lookahead {
+            TerminalOrFinish::Terminal(t) => format!("`{}`", t.0),
+            TerminalOrFinish::Finish => String::from("EOF"),
+        };
+        writeln!(f, "in state {} on lookahead {}:", self.state, lookahead)?;
+        for rule in &self.shift {
+            writeln!(f, "  shift {} in rule {}", lookahead, rule)?;
+        }
+        for rule in &self.reduce {
+            writeln!(f, "  reduce by rule {}", rule)?;
+        }
+        let actions = self.shift.len() + self.reduce.len();
+        writeln!(
+            f,
+            "{}",
+            counterexample::counterexamples_to_string(&self.counterexamples, actions)
+        )?;
+        write!(f, "Items of state {}:", self.state)?;
+        for item in &self.items {
+            write!(f, "\n  {}", item)?;
+        }
+        Ok(())
+    }
 }
 
 impl Display for GeneratorError {
@@ -31,19 +274,79 @@ impl Display for GeneratorError {
                     TerminalOrFinish::Finish => "EOF",
                 };
                 format!(
-                    "Unexpected token {} at {}:{}-{}:{}",
-                    name, begin.line, begin.column, end.line, end.column,
+                    "Unexpected token {} at {}:{}-{}:{}, {}",
+                    name,
+                    begin.line,
+                    begin.column,
+                    end.line,
+                    end.column,
+                    err.expected_to_string(),
                 )
             }
-            GeneratorError::ShiftReduceConflict => {
-                String::from("Encountered shift-reduce conflict while generating tables")
+            GeneratorError::UnterminatedLiteral(_) => String::from("Unterminated literal"),
+            GeneratorError::ShiftReduceConflict(conflict) => {
+                format!("Shift-reduce conflict {}", conflict)
             }
-            GeneratorError::ReduceReduceConflict => {
-                String::from("Encountered reduce-reduce conflict while generating tables")
+            GeneratorError::ReduceReduceConflict(conflict) => {
+                format!("Reduce-reduce conflict {}", conflict)
+            }
+            GeneratorError::Conflicts(conflicts) => {
+                let mut res = format!(
+                    "Found {} conflicts while generating tables",
+                    conflicts.len()
+                );
+                for conflict in conflicts {
+                    res += format!("\n\n{}", conflict).as_ref();
+                }
+                res
             }
-            GeneratorError::UndeclaredNonterminal(nterm) => {
+            GeneratorError::UndeclaredNonterminal(nterm, _) => {
                 format!("Use of undeclared nonterminal: {}", nterm.0)
             }
+            GeneratorError::UndeclaredPrecedence { terminal, .. } => {
+                format!("Terminal {} in prec has no precedence level", terminal.0)
+            }
+            GeneratorError::InvalidRegex { regex, message, .. } => {
+                format!("Invalid regular expression \"{}\": {}", regex, message)
+            }
+            GeneratorError::DuplicateToken { terminal, .. } => {
+                format!("Token {} is declared more than once", terminal.0)
+            }
+            GeneratorError::DuplicateDefinition { name, .. } => {
+                format!("{} is declared more than once", name)
+            }
+            GeneratorError::ImportError { path, message, .. } => {
+                format!("Can not import {}: {}", path, message)
+            }
+            GeneratorError::MissingAction(rule, _) => {
+                format!("Missing action for rule {} of nonterminal with type", rule)
+            }
+            GeneratorError::InvalidActionReference { rule, index, .. } => {
+                format!("Invalid reference ${} in action of rule {}", index, rule)
+            }
+            GeneratorError::LL1Conflict {
+                nonterminal,
+                lookahead,
+                rules,
+                ..
+            } => {
+                let lookahead = match lookahead {
+                    TerminalOrFinish::Terminal(t) => format!("`{}`", t.0),
+                    TerminalOrFinish::Finish => String::from("EOF"),
+                };
+                let mut res = format!(
+                    "LL(1) conflict for nonterminal {} on lookahead {}:",
+                    nonterminal.0, lookahead
+                );
+                for rule in rules {
+                    res += format!("\n  predict rule {}", rule).as_ref();
+                }
+                res
+            }
+            GeneratorError::Lints(lints) => {
+                let lints: Vec<String> = lints.iter().map(|x| x.to_string()).collect();
+                lints.join("\n")
+            }
         };
         f.write_str(&res)
     }
@@ -55,49 +358,11 @@ impl From<ParseError<TokenAttribute>> for GeneratorError {
     }
 }
 
-impl ToString for Term {
-    fn to_string(&self) -> String {
+impl Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Term::Nonterminal(Nonterminal(s)) => s.clone(),
-            Term::Terminal(Terminal(s)) => s.clone(),
-        }
-    }
-}
-
-#[derive(PartialEq, Eq, Hash, Clone, Debug)]
-enum TermOrEmpty {
-    Term(Term),
-    Empty,
-}
-
-#[derive(PartialEq, Eq, Hash, Clone, Debug)]
-enum TermOrFinish {
-    Term(Term),
-    Finish,
-}
-
-impl TermOrFinish {
-    fn from_terminal_or_finish(term: &TerminalOrFinish) -> TermOrFinish {
-        match term {
-            TerminalOrFinish::Finish => TermOrFinish::Finish,
-            TerminalOrFinish::Terminal(t) => TermOrFinish::Term(Term::Terminal(t.clone())),
-        }
-    }
-}
-
-impl TerminalOrFinish {
-    fn from_terminal_or_empty(term: &TerminalOrEmpty) -> Option<TerminalOrFinish> {
-        match term {
-            TerminalOrEmpty::Terminal(t) => Some(TerminalOrFinish::Terminal(t.clone())),
-            TerminalOrEmpty::Empty => None,
-        }
-    }
-
-    fn from_term_or_finish(term: &TermOrFinish) -> Option<TerminalOrFinish> {
-        match term {
-            TermOrFinish::Term(Term::Terminal(t)) => Some(TerminalOrFinish::Terminal(t.clone())),
-            TermOrFinish::Finish => Some(TerminalOrFinish::Finish),
-            _ => None,
+            Term::Nonterminal(Nonterminal(s)) => f.write_str(s),
+            Term::Terminal(Terminal(s)) => f.write_str(s),
         }
     }
 }
@@ -108,28 +373,28 @@ enum TerminalOrEmpty {
     Empty,
 }
 
-impl ToString for Rule {
-    fn to_string(&self) -> String {
-        let mut result = format!("{} -> ", self.left.0);
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ->", self.left.0)?;
         for term in &self.right {
-            result += term.to_string().as_ref();
+            write!(f, " {}", term)?;
         }
-        result
+        Ok(())
     }
 }
 
 impl Rule {
     fn to_literal(&self) -> String {
-        let nterm = format!("Nonterminal(String::from(\"{}\"))", self.left.0);
+        let nterm = format!("Nonterminal(String::from({:?}))", self.left.0);
         let mut right = String::new();
         for term in &self.right {
             right += match term {
                 Term::Nonterminal(nterm) => format!(
-                    "Term::Nonterminal(Nonterminal(String::from(\"{}\")))",
+                    "Term::Nonterminal(Nonterminal(String::from({:?})))",
                     nterm.0
                 ),
                 Term::Terminal(term) => {
-                    format!("Term::Terminal(Terminal(String::from(\"{}\")))", term.0)
+                    format!("Term::Terminal(Terminal(String::from({:?})))", term.0)
                 }
             }
             .as_ref();
@@ -147,14 +412,144 @@ impl Rule {
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Debug)]
 pub struct Grammar {
     axiom: Nonterminal,
+    /// other nonterminals declared in `axiom` that the tables can parse from
+    entries: Vec<Nonterminal>,
     rules: Vec<Rule>,
+    precedence: Precedence,
+    tokens: Vec<TokenDeclaration>,
+    semantics: Semantics,
+    /// fragments of the rules by their indices, empty for grammars built in code
+    spans: Vec<RuleSpan>,
+    /// terminals written as bare reserved words, quoted ones are not kept
+    reserved: Vec<(Terminal, Fragment)>,
+}
+
+/// Grammars are equal if they describe the same rules, wherever they are in the source.
+impl PartialEq for Grammar {
+    fn eq(&self, other: &Self) -> bool {
+        self.axiom == other.axiom
+            && self.entries == other.entries
+            && self.rules == other.rules
+            && self.precedence == other.precedence
+            && self.tokens == other.tokens
+            && self.semantics == other.semantics
+    }
+}
+
+impl Eq for Grammar {}
+
+/// Fragment of a rule in the grammar source and fragments of the symbols of its right
+/// part. Rules of EBNF operators point to the item they are generated from.
+#[derive(Debug, Clone)]
+struct RuleSpan {
+    rule: Fragment,
+    right: Vec<Fragment>,
+    /// terminal of the `prec` annotation
+    prec: Option<Fragment>,
+}
+
+impl Grammar {
+    /// Reads the grammar from a string, imports are relative to the current directory.
+    pub fn from_string(input: &str) -> Result<Grammar, GeneratorError> {
+        Grammar::from_sources(&mut vec![Source::new("", input)])
+    }
+
+    /// Reads the grammar from the first source and the files it imports, paths of imports
+    /// are relative to the importing file. Imported files are appended to the sources.
+    pub fn from_sources(sources: &mut Vec<Source>) -> Result<Grammar, GeneratorError> {
+        let tokens = get_grammar_tokens(&sources[0].text, 0)?;
+        let tables = parser::get_parse_tables();
+        let tree = ParseTree::from_tables_and_tokens(&tables, &tokens)?;
+        get_grammar_from_tree(&tree, sources)
+    }
+
+    /// Returns true if the grammar has token or skip declarations, so a lexer can be generated.
+    pub fn declares_tokens(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    /// Returns the fragment of the first occurrence of the rule in the source.
+    fn get_rule_fragment(&self, rule: &Rule) -> Option<Fragment> {
+        let index = self.rules.iter().position(|x| x == rule)?;
+        self.spans.get(index).map(|x| x.rule.clone())
+    }
+}
+
+/// Regex of a terminal, declarations without a terminal describe skipped input.
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct TokenDeclaration {
+    terminal: Option<Terminal>,
+    regex: String,
+    fragment: Fragment,
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Associativity {
+    Left,
+    Right,
+    NonAssoc,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum Resolution {
+    Shift,
+    Reduce,
+    Error,
+}
+
+/// Precedence levels of terminals and explicit precedence of rules.
+/// Terminals declared later bind tighter.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Precedence {
+    levels: u32,
+    terminals: HashMap<Terminal, (u32, Associativity)>,
+    rules: HashMap<Rule, Terminal>,
+}
+
+impl Precedence {
+    fn add_level(&mut self, associativity: Associativity, terminals: Vec<Terminal>) {
+        self.levels += 1;
+        for term in terminals {
+            self.terminals.insert(term, (self.levels, associativity));
+        }
+    }
+
+    fn get_rule_precedence(&self, rule: &Rule) -> Option<u32> {
+        if let Some(term) = self.rules.get(rule) {
+            return self.terminals.get(term).map(|x| x.0);
+        }
+        rule.right.iter().rev().find_map(|term| match term {
+            Term::Terminal(t) => self.terminals.get(t).map(|x| x.0),
+            Term::Nonterminal(_) => None,
+        })
+    }
+
+    fn resolve(&self, rule: &Rule, lookahead: &TerminalOrFinish) -> Option<Resolution> {
+        let (term_level, associativity) = match lookahead {
+            TerminalOrFinish::Terminal(t) => *self.terminals.get(t)?,
+            TerminalOrFinish::Finish => return None,
+        };
+        let rule_level = self.get_rule_precedence(rule)?;
+        let resolution = if term_level > rule_level {
+            Resolution::Shift
+        } else if term_level < rule_level {
+            Resolution::Reduce
+        } else {
+            match associativity {
+                Associativity::Left => Resolution::Reduce,
+                Associativity::Right => Resolution::Shift,
+                Associativity::NonAssoc => Resolution::Error,
+            }
+        };
+        Some(resolution)
+    }
 }
 
 #[derive(PartialEq, Eq, Hash, Debug, Clone, PartialOrd, Ord)]
-struct LR1Item {
+pub struct LR1Item {
     rule: Rule,
     position: u32,
     lookup: TerminalOrFinish,
@@ -164,25 +559,30 @@ impl LR1Item {
     fn is_finish(&self) -> bool {
         self.position == self.rule.right.len() as u32
     }
+
+    fn next_term(&self) -> Option<&Term> {
+        self.rule.right.get(self.position as usize)
+    }
 }
 
-#[derive(PartialEq, Eq, Hash, Debug, Clone, PartialOrd, Ord)]
+/// Core of an item, LALR tables merge states with equal sets of cores.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, PartialOrd, Ord)]
 struct LR0Item {
-    rule: Rule,
+    rule: u32,
     position: u32,
 }
 
 impl LR0Item {
-    fn from_lr1_item(item: &LR1Item) -> LR0Item {
+    fn from_item(item: &Item) -> LR0Item {
         LR0Item {
-            rule: item.rule.clone(),
+            rule: item.rule,
             position: item.position,
         }
     }
 }
 
-impl ToString for LR1Item {
-    fn to_string(&self) -> String {
+impl Display for LR1Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut right_str = String::new();
         let mut i = 0;
         for term in &self.rule.right {
@@ -196,124 +596,135 @@ impl ToString for LR1Item {
         if i == self.position {
             right_str.push('^');
         }
-        format!(
-            "{} -> {}, {}",
-            self.rule.left.0,
-            right_str,
-            self.lookup.to_string()
-        )
+        write!(f, "{} -> {}, {}", self.rule.left.0, right_str, self.lookup)
     }
 }
 
+/// Edges labeled with `None` are epsilon transitions.
 pub struct NonDeterministicLR1Automaton {
-    edges: HashMap<LR1Item, HashMap<LR1Item, TermOrEmpty>>,
-    start: LR1Item,
+    symbols: SymbolTable,
+    edges: HashMap<Item, Vec<(Item, Option<SymbolId>)>>,
+    start: Item,
+    /// start items of the other entries of the grammar
+    entries: Vec<Item>,
+    precedence: Precedence,
 }
 
 impl NonDeterministicLR1Automaton {
     pub fn from_grammar(grammar: &Grammar) -> NonDeterministicLR1Automaton {
-        let mut by_left: HashMap<Nonterminal, Vec<Rule>> = HashMap::new();
-        for rule in &grammar.rules {
-            match by_left.get_mut(&rule.left) {
-                Some(v) => {
-                    v.push(rule.clone());
-                }
-                None => {
-                    by_left.insert(rule.left.clone(), vec![rule.clone()]);
-                }
+        let symbols = SymbolTable::from_grammar(grammar);
+        let first = Self::get_first_ids(&symbols);
+        let mut entries = Self::get_start_items(&symbols);
+        let start = entries.remove(0);
+        let mut edges = HashMap::new();
+        let mut stack = entries.clone();
+        stack.push(start);
+        while let Some(cur) = stack.pop() {
+            if edges.contains_key(&cur) {
+                continue;
             }
+            let adjacent = Self::get_adjacent(&symbols, &first, &cur);
+            stack.extend(adjacent.iter().map(|(x, _)| *x));
+            edges.insert(cur, adjacent);
         }
-        let mut edges = HashMap::new();
-        let first = calculate_first(grammar);
-        let start = &LR1Item {
-            rule: by_left[&Nonterminal(String::from(GRAMMAR_AXIOM_NAME))][0].clone(),
-            position: 0,
-            lookup: TerminalOrFinish::Finish,
-        };
-        Self::from_grammar_rec(start, &mut edges, &by_left, &first);
         NonDeterministicLR1Automaton {
+            symbols,
             edges,
-            start: start.clone(),
+            start,
+            entries,
+            precedence: grammar.precedence.clone(),
         }
     }
 
-    fn from_grammar_rec(
-        cur: &LR1Item,
-        edges: &mut HashMap<LR1Item, HashMap<LR1Item, TermOrEmpty>>,
-        by_left: &HashMap<Nonterminal, Vec<Rule>>,
-        first: &HashMap<Nonterminal, HashSet<TerminalOrEmpty>>,
-    ) {
-        if edges.contains_key(cur) {
-            return;
-        }
-        edges.insert(cur.clone(), HashMap::new());
-        if !cur.is_finish() {
-            let term = &cur.rule.right[cur.position as usize];
-            let next = LR1Item {
-                rule: cur.rule.clone(),
-                position: cur.position + 1,
-                lookup: cur.lookup.clone(),
-            };
-            let fst = edges.get_mut(cur).expect("no first set");
-            fst.insert(next.clone(), TermOrEmpty::Term(term.clone()));
-            Self::from_grammar_rec(&next, edges, by_left, first);
-            if let Term::Nonterminal(nterm) = term {
-                let mut next_terms: Vec<TermOrFinish> = cur.rule.right
-                    [(cur.position + 1) as usize..]
+    /// First sets of nonterminals by id, the flag is true for nullable ones.
+    fn get_first_ids(symbols: &SymbolTable) -> Vec<(Vec<u32>, bool)> {
+        let first = calculate_first_of_rules(symbols.rules());
+        (0..symbols.nonterminal_count() as u32)
+            .map(|id| {
+                let set = &first[symbols.nonterminal(id)];
+                let terminals = set
                     .iter()
-                    .map(|x| TermOrFinish::Term(x.clone()))
+                    .filter_map(|x| match x {
+                        TerminalOrEmpty::Terminal(t) => symbols.get_terminal_id(t),
+                        TerminalOrEmpty::Empty => None,
+                    })
                     .collect();
-                next_terms.push(TermOrFinish::from_terminal_or_finish(&cur.lookup));
-                for term in next_terms {
-                    let lookups: HashSet<TerminalOrFinish>;
-                    let is_nullable;
-                    match term {
-                        TermOrFinish::Term(Term::Nonterminal(nt)) => {
-                            lookups = first[&nt]
-                                .iter()
-                                .filter_map(TerminalOrFinish::from_terminal_or_empty)
-                                .collect();
-                            is_nullable = first[&nt].contains(&TerminalOrEmpty::Empty);
-                        }
-                        _ => {
-                            let a = TerminalOrFinish::from_term_or_finish(&term)
-                                .expect("failed type conversion");
-                            lookups = [a].into_iter().collect();
-                            is_nullable = false;
-                        }
-                    }
-                    for lookup in &lookups {
-                        for rule in &by_left[nterm] {
-                            let next = LR1Item {
-                                rule: rule.clone(),
-                                position: 0,
-                                lookup: lookup.clone(),
-                            };
-                            let fst = edges.get_mut(cur).expect("no first set");
-                            fst.insert(next.clone(), TermOrEmpty::Empty);
-                            Self::from_grammar_rec(&next, edges, by_left, first);
-                        }
-                    }
-                    if !is_nullable {
-                        break;
-                    }
+                (terminals, set.contains(&TerminalOrEmpty::Empty))
+            })
+            .collect()
+    }
+
+    /// Items of the rules of the fake axiom, the first one is of the axiom.
+    fn get_start_items(symbols: &SymbolTable) -> Vec<Item> {
+        let axiom = symbols
+            .get_nonterminal_id(&Nonterminal(String::from(GRAMMAR_AXIOM_NAME)))
+            .expect("no fake axiom in grammar");
+        symbols
+            .rules_of(axiom)
+            .iter()
+            .map(|rule| Item {
+                rule: *rule,
+                position: 0,
+                lookup: 0,
+            })
+            .collect()
+    }
+
+    fn get_adjacent(
+        symbols: &SymbolTable,
+        first: &[(Vec<u32>, bool)],
+        cur: &Item,
+    ) -> Vec<(Item, Option<SymbolId>)> {
+        let Some(symbol) = symbols.next_symbol(cur) else {
+            return Vec::new();
+        };
+        let next = Item {
+            position: cur.position + 1,
+            ..*cur
+        };
+        let mut res = vec![(next, Some(symbol))];
+        let SymbolId::Nonterminal(nterm) = symbol else {
+            return res;
+        };
+        let mut lookups = BTreeSet::new();
+        let mut is_nullable = true;
+        for next_symbol in &symbols.right(cur.rule)[cur.position as usize + 1..] {
+            match next_symbol {
+                SymbolId::Terminal(t) => {
+                    lookups.insert(*t as u32);
+                    is_nullable = false;
                 }
+                SymbolId::Nonterminal(n) => {
+                    lookups.extend(first[*n].0.iter().copied());
+                    is_nullable = first[*n].1;
+                }
+            }
+            if !is_nullable {
+                break;
+            }
+        }
+        if is_nullable {
+            lookups.insert(cur.lookup);
+        }
+        for lookup in lookups {
+            for rule in symbols.rules_of(nterm as u32) {
+                let item = Item {
+                    rule: *rule,
+                    position: 0,
+                    lookup,
+                };
+                res.push((item, None));
             }
         }
+        res
     }
-}
 
-impl NonDeterministicLR1Automaton {
-    fn get_transitions(&self, vertices: &BTreeSet<LR1Item>) -> HashMap<Term, BTreeSet<LR1Item>> {
-        let mut res: HashMap<Term, BTreeSet<LR1Item>> = HashMap::new();
+    fn get_transitions(&self, vertices: &BTreeSet<Item>) -> HashMap<SymbolId, BTreeSet<Item>> {
+        let mut res: HashMap<SymbolId, BTreeSet<Item>> = HashMap::new();
         for vertex in vertices {
-            for (other, term) in &self.edges[vertex] {
-                if let TermOrEmpty::Term(t) = term {
-                    if !res.contains_key(t) {
-                        res.insert(t.clone(), BTreeSet::new());
-                    }
-                    let fst = res.get_mut(t).expect("no set for vertex");
-                    fst.insert(other.clone());
+            for (other, symbol) in &self.edges[vertex] {
+                if let Some(symbol) = symbol {
+                    res.entry(*symbol).or_default().insert(*other);
                 }
             }
         }
@@ -322,127 +733,121 @@ impl NonDeterministicLR1Automaton {
             .collect()
     }
 
-    fn get_epsilon_closure(&self, vertices: &BTreeSet<LR1Item>) -> BTreeSet<LR1Item> {
-        let mut res = BTreeSet::new();
-        for vertex in vertices {
-            if !res.contains(vertex) {
-                self.get_epsilon_closure_rec(vertex, &mut res);
-            }
-        }
-        res
-    }
-
-    fn get_epsilon_closure_rec(&self, vertex: &LR1Item, res: &mut BTreeSet<LR1Item>) {
-        res.insert(vertex.clone());
-        for (other, term) in &self.edges[vertex] {
-            if let TermOrEmpty::Empty = term {
-                if !res.contains(other) {
-                    self.get_epsilon_closure_rec(other, res);
+    fn get_epsilon_closure(&self, vertices: &BTreeSet<Item>) -> BTreeSet<Item> {
+        let mut res = vertices.clone();
+        let mut stack: Vec<Item> = vertices.iter().copied().collect();
+        while let Some(cur) = stack.pop() {
+            for (other, symbol) in &self.edges[&cur] {
+                if symbol.is_none() && res.insert(*other) {
+                    stack.push(*other);
                 }
             }
         }
+        res
     }
 }
 
 pub struct DetermenisticLR1Automaton {
-    edges: HashMap<BTreeSet<LR1Item>, HashMap<BTreeSet<LR1Item>, Term>>,
-    start: BTreeSet<LR1Item>,
+    symbols: SymbolTable,
+    edges: HashMap<BTreeSet<Item>, HashMap<BTreeSet<Item>, SymbolId>>,
+    start: BTreeSet<Item>,
+    /// start states of the other entries of the grammar
+    entries: Vec<BTreeSet<Item>>,
+    precedence: Precedence,
 }
 
 impl DetermenisticLR1Automaton {
-    pub fn from_non_deterministic(
-        automaton: &NonDeterministicLR1Automaton,
+    pub fn from_string(input: &str) -> Result<DetermenisticLR1Automaton, GeneratorError> {
+        let grammar = Grammar::from_string(input)?;
+        Ok(DetermenisticLR1Automaton::from_grammar(&grammar))
+    }
+
+    pub fn from_grammar(grammar: &Grammar) -> DetermenisticLR1Automaton {
+        let nfa = NonDeterministicLR1Automaton::from_grammar(grammar);
+        DetermenisticLR1Automaton::from_non_deterministic(&nfa)
+    }
+
+    /// Builds the automaton with the states of the tables of the type.
+    pub fn from_grammar_for(
+        grammar: &Grammar,
+        tables_type: ParseTablesType,
     ) -> DetermenisticLR1Automaton {
-        let mut edges = HashMap::new();
-        let mut start: BTreeSet<LR1Item> = [automaton.start.clone()].into_iter().collect();
-        start = automaton.get_epsilon_closure(&start);
-        Self::from_non_deterministic_rec(&start, automaton, &mut edges);
-        DetermenisticLR1Automaton { edges, start }
+        match tables_type {
+            ParseTablesType::LR1 => DetermenisticLR1Automaton::from_grammar(grammar),
+            ParseTablesType::LALR => DetermenisticLR1Automaton::lalr_from_grammar(grammar),
+            ParseTablesType::MinimalLR1 => DetermenisticLR1Automaton::minimal_from_grammar(grammar),
+            ParseTablesType::SLR => DetermenisticLR1Automaton::slr_from_grammar(grammar),
+            ParseTablesType::LR0 => DetermenisticLR1Automaton::lr0_from_grammar(grammar),
+        }
     }
 
-    fn from_non_deterministic_rec(
-        cur: &BTreeSet<LR1Item>,
+    pub fn from_non_deterministic(
         automaton: &NonDeterministicLR1Automaton,
-        edges: &mut HashMap<BTreeSet<LR1Item>, HashMap<BTreeSet<LR1Item>, Term>>,
-    ) {
-        edges.insert(cur.clone(), HashMap::new());
-        for (term, other) in &automaton.get_transitions(cur) {
-            let edges_ref = edges.get_mut(cur).expect("state is absent from map");
-            edges_ref.insert(other.clone(), term.clone());
-            if !edges.contains_key(other) {
-                Self::from_non_deterministic_rec(other, automaton, edges);
+    ) -> DetermenisticLR1Automaton {
+        let mut edges = HashMap::new();
+        let start = automaton.get_epsilon_closure(&BTreeSet::from([automaton.start]));
+        let entries: Vec<BTreeSet<Item>> = automaton
+            .entries
+            .iter()
+            .map(|x| automaton.get_epsilon_closure(&BTreeSet::from([*x])))
+            .collect();
+        let mut stack = entries.clone();
+        stack.push(start.clone());
+        while let Some(cur) = stack.pop() {
+            if edges.contains_key(&cur) {
+                continue;
             }
+            let adjacent: HashMap<BTreeSet<Item>, SymbolId> = automaton
+                .get_transitions(&cur)
+                .into_iter()
+                .map(|(symbol, other)| (other, symbol))
+                .collect();
+            stack.extend(adjacent.keys().cloned());
+            edges.insert(cur, adjacent);
+        }
+        DetermenisticLR1Automaton {
+            symbols: automaton.symbols.clone(),
+            edges,
+            start,
+            entries,
+            precedence: automaton.precedence.clone(),
         }
     }
 }
 
-impl ToString for LR1Action {
-    fn to_string(&self) -> String {
+impl Display for LR1Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Reduce(rule) => format!("Reduce({})", rule.to_string()),
-            Self::Shift(state) => format!("Shift({})", state),
-            Self::Accept => String::from("Accept"),
+            Self::Reduce(rule) => write!(f, "Reduce({})", rule),
+            Self::Shift(state) => write!(f, "Shift({})", state),
+            Self::Accept => f.write_str("Accept"),
         }
     }
 }
 
-fn try_add_action(
-    tables: &mut ParseTables,
-    state: i32,
-    term: TerminalOrFinish,
-    action: LR1Action,
-) -> Result<(), GeneratorError> {
-    if let Some(other_action) = tables.action.get(&(state, term.clone())) {
-        if *other_action != action {
-            let actions = [&action, other_action];
-            for action in actions {
-                if let LR1Action::Shift(_) = action {
-                    return Err(GeneratorError::ShiftReduceConflict);
-                }
-            }
-            return Err(GeneratorError::ReduceReduceConflict);
-        }
-    };
-    tables.action.insert((state, term), action);
-    Ok(())
-}
-
-pub enum ParseTablesType {
-    LR1,
-    LALR,
+/// Collects parse tables from a deterministic automaton, resolving
+/// shift-reduce conflicts with operator precedence where possible.
+struct TablesBuilder<'a> {
+    automaton: &'a DetermenisticLR1Automaton,
+    ids: HashMap<&'a BTreeSet<Item>, i32>,
+    items: HashMap<i32, BTreeSet<Item>>,
+    tables: ParseTables,
+    conflicts: Vec<GeneratorError>,
+    nonassoc_errors: HashSet<(i32, TerminalOrFinish)>,
+    /// actions of conflicts that are not in the tables, kept for GLR tables
+    rejected: HashMap<(i32, TerminalOrFinish), Vec<LR1Action>>,
 }
 
-impl ParseTables {
-    pub fn from_string(
-        input: &str,
-        tables_type: ParseTablesType,
-    ) -> Result<ParseTables, GeneratorError> {
-        let mut lexer = Lexer::new(input);
-        let tokens = lexer.get_tokens();
-        let tables = parser::get_parse_tables();
-        let tree = ParseTree::from_tables_and_tokens(&tables, &tokens)?;
-        let encoded_grammar = get_grammar_from_tree(&tree)?;
-        let nfa = NonDeterministicLR1Automaton::from_grammar(&encoded_grammar);
-        let dfa = DetermenisticLR1Automaton::from_non_deterministic(&nfa);
-        ParseTables::from_automaton(&dfa, tables_type)
-    }
-
-    pub fn from_automaton(
-        automaton: &DetermenisticLR1Automaton,
-        tables_type: ParseTablesType,
-    ) -> Result<ParseTables, GeneratorError> {
+impl<'a> TablesBuilder<'a> {
+    fn new(automaton: &'a DetermenisticLR1Automaton, tables_type: ParseTablesType) -> Self {
         let mut cur = 0;
-        let mut ids: HashMap<&BTreeSet<LR1Item>, i32> = HashMap::new();
+        let mut ids: HashMap<&BTreeSet<Item>, i32> = HashMap::new();
         let mut lr0_ids: HashMap<BTreeSet<LR0Item>, i32> = HashMap::new();
         for item in automaton.edges.keys() {
             match tables_type {
-                ParseTablesType::LR1 => {
-                    ids.insert(item, cur);
-                    cur += 1;
-                }
                 ParseTablesType::LALR => {
                     let lr0_kernel: BTreeSet<LR0Item> =
-                        item.iter().map(LR0Item::from_lr1_item).collect();
+                        item.iter().map(LR0Item::from_item).collect();
                     if !lr0_ids.contains_key(&lr0_kernel) {
                         lr0_ids.insert(lr0_kernel.clone(), cur);
                         cur += 1;
@@ -450,112 +855,377 @@ impl ParseTables {
                     let id = lr0_ids[&lr0_kernel];
                     ids.insert(item, id);
                 }
+                _ => {
+                    ids.insert(item, cur);
+                    cur += 1;
+                }
             }
         }
-        let mut res = ParseTables {
+        let mut items: HashMap<i32, BTreeSet<Item>> = HashMap::new();
+        for (state, id) in &ids {
+            items.entry(*id).or_default().extend(state.iter().copied());
+        }
+        let symbols = &automaton.symbols;
+        let entries = [&automaton.start]
+            .into_iter()
+            .chain(&automaton.entries)
+            .map(|state| {
+                let item = state
+                    .iter()
+                    .find(|x| x.position == 0 && symbols.rule(x.rule).left.0 == GRAMMAR_AXIOM_NAME)
+                    .expect("no axiom item in start state");
+                let Term::Nonterminal(entry) = &symbols.rule(item.rule).right[0] else {
+                    panic!("entry must be nonterminal");
+                };
+                (entry.clone(), ids[state])
+            })
+            .collect();
+        let tables = ParseTables {
             start: ids[&automaton.start],
+            entries,
             action: HashMap::new(),
             goto: HashMap::new(),
         };
-        let mut visited = HashSet::new();
-        Self::from_automaton_rec(&automaton.start, &ids, &mut visited, automaton, &mut res)?;
-        Ok(res)
-    }
-
-    fn from_automaton_rec<'a: 'b, 'b, 'c: 'b>(
-        cur: &'a BTreeSet<LR1Item>,
-        ids: &HashMap<&BTreeSet<LR1Item>, i32>,
-        visited: &mut HashSet<&'b BTreeSet<LR1Item>>,
-        automaton: &'c DetermenisticLR1Automaton,
-        res: &mut ParseTables,
-    ) -> Result<(), GeneratorError> {
-        let id = ids[cur];
-        if visited.contains(cur) {
-            return Ok(());
-        }
-        visited.insert(cur);
-        for (other, term) in &automaton.edges[cur] {
-            let other_id = ids[other];
-            match term {
-                Term::Nonterminal(term) => {
-                    res.goto.insert((id, term.clone()), other_id);
+        TablesBuilder {
+            automaton,
+            ids,
+            items,
+            tables,
+            conflicts: Vec::new(),
+            nonassoc_errors: HashSet::new(),
+            rejected: HashMap::new(),
+        }
+    }
+
+    /// Counterexamples are searched only for reported conflicts, the first one unless `all_conflicts`.
+    fn build(mut self, all_conflicts: bool) -> (ParseTables, Vec<GeneratorError>) {
+        self.add_actions();
+        // actions are added in the order of a hash map
+        self.conflicts.sort_by_key(|x| {
+            let conflict = x.as_conflict().expect("only conflicts are added");
+            (conflict.state, conflict.lookahead.clone())
+        });
+        if !all_conflicts {
+            self.conflicts.truncate(1);
+        }
+        if !self.conflicts.is_empty() {
+            let search = CounterexampleSearch::new(self.automaton);
+            for conflict in &mut self.conflicts {
+                if let Some(conflict) = conflict.as_conflict_mut() {
+                    conflict.counterexamples = search.find_all(&self.ids, conflict);
                 }
-                Term::Terminal(term) => {
-                    try_add_action(
-                        res,
-                        id,
-                        TerminalOrFinish::Terminal(term.clone()),
-                        LR1Action::Shift(other_id),
-                    )?;
+            }
+        }
+        (self.tables, self.conflicts)
+    }
+
+    /// Builds tables with all actions of unresolved conflicts.
+    fn build_glr(mut self) -> GLRParseTables {
+        self.add_actions();
+        let mut action: HashMap<(i32, TerminalOrFinish), Vec<LR1Action>> = self
+            .tables
+            .action
+            .into_iter()
+            .map(|(key, action)| (key, vec![action]))
+            .collect();
+        for (key, rejected) in self.rejected {
+            let actions = action.entry(key).or_default();
+            for other in rejected {
+                if !actions.contains(&other) {
+                    actions.push(other);
+                }
+            }
+        }
+        GLRParseTables {
+            start: self.tables.start,
+            action,
+            goto: self.tables.goto,
+        }
+    }
+
+    fn add_actions(&mut self) {
+        let automaton = self.automaton;
+        let symbols = &automaton.symbols;
+        for (cur, adjacent) in &automaton.edges {
+            let id = self.ids[cur];
+            for (other, symbol) in adjacent {
+                let other_id = self.ids[other];
+                match symbol {
+                    SymbolId::Nonterminal(n) => {
+                        let nterm = symbols.nonterminal(*n as u32).clone();
+                        self.tables.goto.insert((id, nterm), other_id);
+                    }
+                    SymbolId::Terminal(t) => {
+                        let term = symbols.terminal(*t as u32).clone();
+                        self.add_action(id, term, LR1Action::Shift(other_id));
+                    }
+                }
+            }
+            for item in cur {
+                if !symbols.is_finish(item) {
+                    continue;
+                }
+                let rule = symbols.rule(item.rule);
+                if rule.left.0 == GRAMMAR_AXIOM_NAME {
+                    self.add_action(id, TerminalOrFinish::Finish, LR1Action::Accept);
+                } else {
+                    let lookup = symbols.terminal(item.lookup).clone();
+                    self.add_action(id, lookup, LR1Action::Reduce(rule.clone()));
+                }
+            }
+        }
+    }
+
+    fn add_action(&mut self, state: i32, term: TerminalOrFinish, action: LR1Action) {
+        let key = (state, term);
+        if self.nonassoc_errors.contains(&key) {
+            return;
+        }
+        let other_action = match self.tables.action.remove(&key) {
+            Some(other_action) if other_action != action => other_action,
+            _ => {
+                self.tables.action.insert(key, action);
+                return;
+            }
+        };
+        let (shift, reduce) = match (other_action, action) {
+            (shift @ LR1Action::Shift(_), reduce @ LR1Action::Reduce(_))
+            | (reduce @ LR1Action::Reduce(_), shift @ LR1Action::Shift(_)) => (shift, reduce),
+            (other_action, action) => {
+                self.add_conflict(&key, &other_action, &action);
+                self.rejected.entry(key.clone()).or_default().push(action);
+                self.tables.action.insert(key, other_action);
+                return;
+            }
+        };
+        let resolution = match &reduce {
+            LR1Action::Reduce(rule) => self.automaton.precedence.resolve(rule, &key.1),
+            _ => None,
+        };
+        match resolution {
+            Some(Resolution::Shift) => {
+                self.tables.action.insert(key, shift);
+            }
+            Some(Resolution::Reduce) => {
+                self.tables.action.insert(key, reduce);
+            }
+            Some(Resolution::Error) => {
+                self.nonassoc_errors.insert(key);
+            }
+            None => {
+                self.add_conflict(&key, &shift, &reduce);
+                self.rejected.entry(key.clone()).or_default().push(reduce);
+                self.tables.action.insert(key, shift);
+            }
+        }
+    }
+
+    fn add_conflict(
+        &mut self,
+        key: &(i32, TerminalOrFinish),
+        other_action: &LR1Action,
+        action: &LR1Action,
+    ) {
+        let (state, lookahead) = key;
+        // a conflict of three or more actions is reported once with all of them
+        let reported = self.conflicts.iter().position(|x| {
+            x.as_conflict()
+                .is_some_and(|x| x.state == *state && x.lookahead == *lookahead)
+        });
+        let mut conflict = match reported {
+            Some(i) => match self.conflicts.remove(i) {
+                GeneratorError::ShiftReduceConflict(conflict)
+                | GeneratorError::ReduceReduceConflict(conflict) => *conflict,
+                _ => unreachable!(),
+            },
+            None => {
+                let symbols = &self.automaton.symbols;
+                Conflict {
+                    state: *state,
+                    lookahead: lookahead.clone(),
+                    shift: Vec::new(),
+                    reduce: Vec::new(),
+                    items: self.items[state]
+                        .iter()
+                        .map(|x| symbols.resolve(x))
+                        .collect(),
+                    counterexamples: Vec::new(),
+                    fragment: None,
+                }
+            }
+        };
+        for action in [other_action, action] {
+            let rule = match action {
+                LR1Action::Shift(_) => {
+                    conflict.shift = get_shifting_rules(&conflict.items, lookahead);
+                    continue;
+                }
+                LR1Action::Reduce(rule) => rule,
+                // accept reduces by the rule of the fake axiom
+                LR1Action::Accept => {
+                    let item = conflict
+                        .items
+                        .iter()
+                        .find(|x| x.rule.left.0 == GRAMMAR_AXIOM_NAME && x.is_finish())
+                        .expect("no axiom item in accepting state");
+                    &item.rule
                 }
+            };
+            if !conflict.reduce.contains(rule) {
+                conflict.reduce.push(rule.clone());
             }
-            Self::from_automaton_rec(other, ids, visited, automaton, res)?;
         }
-        for item in cur {
-            if item.is_finish() {
-                if item.rule.left.0 == GRAMMAR_AXIOM_NAME {
-                    try_add_action(res, id, TerminalOrFinish::Finish, LR1Action::Accept)
-                        .expect("accept can not have conflicts");
-                } else {
-                    try_add_action(
-                        res,
-                        id,
-                        item.lookup.clone(),
-                        LR1Action::Reduce(item.rule.clone()),
-                    )?;
-                }
-            }
+        self.conflicts.push(if conflict.shift.is_empty() {
+            GeneratorError::ReduceReduceConflict(Box::new(conflict))
+        } else {
+            GeneratorError::ShiftReduceConflict(Box::new(conflict))
+        });
+    }
+}
+
+fn get_shifting_rules(items: &BTreeSet<LR1Item>, term: &TerminalOrFinish) -> Vec<Rule> {
+    let mut rules: Vec<Rule> = Vec::new();
+    for item in items {
+        let shifts = match (item.next_term(), term) {
+            (Some(Term::Terminal(t)), TerminalOrFinish::Terminal(other)) => t == other,
+            _ => false,
+        };
+        if shifts && !rules.contains(&item.rule) {
+            rules.push(item.rule.clone());
+        }
+    }
+    rules
+}
+
+/// Given an automaton, only `LALR` merges its states, others keep them as is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseTablesType {
+    LR1,
+    LALR,
+    /// LR(1) states with equal cores are merged unless it causes conflicts.
+    MinimalLR1,
+    /// LR(0) states, rules are reduced on FOLLOW sets of their left sides.
+    SLR,
+    /// LR(0) states, rules are reduced on any lookahead.
+    LR0,
+}
+
+impl ParseTables {
+    pub fn from_string(
+        input: &str,
+        tables_type: ParseTablesType,
+    ) -> Result<ParseTables, GeneratorError> {
+        let grammar = Grammar::from_string(input)?;
+        ParseTables::from_grammar(&grammar, tables_type)
+    }
+
+    pub fn from_string_all_conflicts(
+        input: &str,
+        tables_type: ParseTablesType,
+    ) -> Result<ParseTables, GeneratorError> {
+        let grammar = Grammar::from_string(input)?;
+        ParseTables::from_grammar_all_conflicts(&grammar, tables_type)
+    }
+
+    pub fn from_grammar(
+        grammar: &Grammar,
+        tables_type: ParseTablesType,
+    ) -> Result<ParseTables, GeneratorError> {
+        let (tables, conflicts) = Self::from_grammar_with_conflicts(grammar, tables_type, false);
+        match conflicts.into_iter().next() {
+            Some(conflict) => Err(conflict),
+            None => Ok(tables),
+        }
+    }
+
+    pub fn from_grammar_all_conflicts(
+        grammar: &Grammar,
+        tables_type: ParseTablesType,
+    ) -> Result<ParseTables, GeneratorError> {
+        let (tables, conflicts) = Self::from_grammar_with_conflicts(grammar, tables_type, true);
+        if conflicts.is_empty() {
+            Ok(tables)
+        } else {
+            Err(GeneratorError::Conflicts(conflicts))
+        }
+    }
+
+    /// LALR tables are built from the LR(0) automaton with propagated lookaheads,
+    /// conflicts and counterexamples are reported from the same automaton.
+    fn from_grammar_with_conflicts(
+        grammar: &Grammar,
+        tables_type: ParseTablesType,
+        all_conflicts: bool,
+    ) -> (ParseTables, Vec<GeneratorError>) {
+        let automaton = DetermenisticLR1Automaton::from_grammar_for(grammar, tables_type);
+        // states are already merged
+        let mut res =
+            Self::from_automaton_with_conflicts(&automaton, ParseTablesType::LR1, all_conflicts);
+        for conflict in res.1.iter_mut().filter_map(|x| x.as_conflict_mut()) {
+            let rule = conflict.reduce.first();
+            conflict.fragment = rule.and_then(|x| grammar.get_rule_fragment(x));
+        }
+        res
+    }
+
+    pub fn from_automaton(
+        automaton: &DetermenisticLR1Automaton,
+        tables_type: ParseTablesType,
+    ) -> Result<ParseTables, GeneratorError> {
+        let (tables, conflicts) =
+            Self::from_automaton_with_conflicts(automaton, tables_type, false);
+        match conflicts.into_iter().next() {
+            Some(conflict) => Err(conflict),
+            None => Ok(tables),
         }
-        Ok(())
+    }
+
+    pub fn from_automaton_all_conflicts(
+        automaton: &DetermenisticLR1Automaton,
+        tables_type: ParseTablesType,
+    ) -> Result<ParseTables, GeneratorError> {
+        let (tables, conflicts) = Self::from_automaton_with_conflicts(automaton, tables_type, true);
+        if conflicts.is_empty() {
+            Ok(tables)
+        } else {
+            Err(GeneratorError::Conflicts(conflicts))
+        }
+    }
+
+    fn from_automaton_with_conflicts(
+        automaton: &DetermenisticLR1Automaton,
+        tables_type: ParseTablesType,
+        all_conflicts: bool,
+    ) -> (ParseTables, Vec<GeneratorError>) {
+        TablesBuilder::new(automaton, tables_type).build(all_conflicts)
     }
 
     pub fn to_rust_source(&self) -> String {
-        let parser_source = include_bytes!("parser.rs");
-        let parser_source: Vec<String> = String::from_utf8_lossy(parser_source)
-            .lines()
-            .map(|x| x.to_string())
-            .collect();
-        let start_index = parser_source
-            .iter()
-            .position(|x| x.starts_with("//@START_PARSE_TABLES@"))
-            .expect("no @START_PARSE_TABLES@ comment in parser.rs");
-        let finish_index = parser_source
-            .iter()
-            .position(|x| x.starts_with("//@END_PARSE_TABLES@"))
-            .expect("no @END_Pno @END_PARSE_TABLES@ comment in parser.rs");
-        let tables = self.to_rust_function();
-        [
-            parser_source[0..=start_index].join("\n"),
-            tables,
-            parser_source[finish_index..parser_source.len()].join("\n"),
-        ]
-        .join("\n")
+        self.to_rust_source_with(&[])
+    }
+
+    /// Like `to_rust_source`, but also emits the given code after `get_parse_tables()`,
+    /// e.g. the lexer or the actions.
+    pub fn to_rust_source_with(&self, extra: &[String]) -> String {
+        with_runtime(&self.to_rust_function(), extra)
     }
 
     fn to_rust_function(&self) -> String {
+        let mut entries = String::new();
+        for (nterm, state) in &self.entries {
+            let nterm = format!("Nonterminal(String::from({:?}))", nterm.0);
+            entries += format!("({}, {}),\n", nterm, state).as_ref();
+        }
         let mut action_entries = String::new();
-        let mut goto_entries = String::new();
         for ((state, term), action) in &self.action {
-            let term_str = match term {
-                TerminalOrFinish::Terminal(Terminal(s)) => format!(
-                    "TerminalOrFinish::Terminal(Terminal(String::from(\"{}\")))",
-                    s
-                ),
-                TerminalOrFinish::Finish => String::from("TerminalOrFinish::Finish"),
-            };
-            let action_str = match action {
-                LR1Action::Shift(state) => format!("LR1Action::Shift({})", state),
-                LR1Action::Reduce(rule) => format!("LR1Action::Reduce({})", rule.to_literal()),
-                LR1Action::Accept => String::from("LR1Action::Accept"),
-            };
-            let entry = format!("(({}, {}), {}),\n", state, term_str, action_str);
+            let entry = format!(
+                "(({}, {}), {}),\n",
+                state,
+                terminal_or_finish_to_literal(term),
+                action_to_literal(action)
+            );
             action_entries += entry.as_ref();
         }
-        for ((cur_state, nterm), next_state) in &self.goto {
-            let nterm = format!("Nonterminal(String::from(\"{}\"))", nterm.0);
-            let entry = format!("(({}, {}), {}),\n", cur_state, nterm, next_state);
-            goto_entries += entry.as_ref();
-        }
         format!(
             r#"
         pub fn get_parse_tables() -> ParseTables {{
@@ -565,25 +1235,88 @@ impl ParseTables {
             let goto = [
                 {}
             ].into_iter().collect();
+            let entries = [
+                {}
+            ].into_iter().collect();
             ParseTables {{
                 start: {},
+                entries,
                 action,
                 goto,
             }}
         }}
         "#,
-            action_entries, goto_entries, self.start
+            action_entries,
+            goto_to_literal(&self.goto),
+            entries,
+            self.start
         )
     }
 }
 
+fn terminal_or_finish_to_literal(term: &TerminalOrFinish) -> String {
+    match term {
+        TerminalOrFinish::Terminal(Terminal(s)) => format!(
+            "TerminalOrFinish::Terminal(Terminal(String::from({:?})))",
+            s
+        ),
+        TerminalOrFinish::Finish => String::from("TerminalOrFinish::Finish"),
+    }
+}
+
+fn action_to_literal(action: &LR1Action) -> String {
+    match action {
+        LR1Action::Shift(state) => format!("LR1Action::Shift({})", state),
+        LR1Action::Reduce(rule) => format!("LR1Action::Reduce({})", rule.to_literal()),
+        LR1Action::Accept => String::from("LR1Action::Accept"),
+    }
+}
+
+fn goto_to_literal(goto: &HashMap<(i32, Nonterminal), i32>) -> String {
+    let mut goto_entries = String::new();
+    for ((cur_state, nterm), next_state) in goto {
+        let nterm = format!("Nonterminal(String::from({:?}))", nterm.0);
+        let entry = format!("(({}, {}), {}),\n", cur_state, nterm, next_state);
+        goto_entries += entry.as_ref();
+    }
+    goto_entries
+}
+
+/// Inserts the tables function and the extra code into the runtime part of parser.rs.
+pub(crate) fn with_runtime(tables: &str, extra: &[String]) -> String {
+    let parser_source = include_bytes!("parser.rs");
+    let parser_source: Vec<String> = String::from_utf8_lossy(parser_source)
+        .lines()
+        .map(|x| x.to_string())
+        .collect();
+    let start_index = parser_source
+        .iter()
+        .position(|x| x.starts_with("//@START_PARSE_TABLES@"))
+        .expect("no @START_PARSE_TABLES@ comment in parser.rs");
+    let finish_index = parser_source
+        .iter()
+        .position(|x| x.starts_with("//@END_PARSE_TABLES@"))
+        .expect("no @END_Pno @END_PARSE_TABLES@ comment in parser.rs");
+    [
+        parser_source[0..=start_index].join("\n"),
+        String::from(tables),
+        extra.join("\n"),
+        parser_source[finish_index..parser_source.len()].join("\n"),
+    ]
+    .join("\n")
+}
+
 fn calculate_first(grammar: &Grammar) -> HashMap<Nonterminal, HashSet<TerminalOrEmpty>> {
+    calculate_first_of_rules(&grammar.rules)
+}
+
+fn calculate_first_of_rules(rules: &[Rule]) -> HashMap<Nonterminal, HashSet<TerminalOrEmpty>> {
     let mut first = HashMap::new();
     let mut nullable: HashSet<Nonterminal> = HashSet::new();
     let mut changed = true;
     while changed {
         changed = false;
-        for rule in &grammar.rules {
+        for rule in rules {
             if nullable.contains(&rule.left) {
                 continue;
             }
@@ -601,13 +1334,13 @@ fn calculate_first(grammar: &Grammar) -> HashMap<Nonterminal, HashSet<TerminalOr
             }
         }
     }
-    for rule in &grammar.rules {
-        if first.get(&rule.left).is_none() {
+    for rule in rules {
+        if !first.contains_key(&rule.left) {
             first.insert(rule.left.clone(), HashSet::new());
         }
         for term in &rule.right {
             if let Term::Nonterminal(term) = term {
-                if first.get(term).is_none() {
+                if !first.contains_key(term) {
                     first.insert(term.clone(), HashSet::new());
                 }
             }
@@ -620,7 +1353,7 @@ fn calculate_first(grammar: &Grammar) -> HashMap<Nonterminal, HashSet<TerminalOr
     changed = true;
     while changed {
         changed = false;
-        for rule in &grammar.rules {
+        for rule in rules {
             for term in &rule.right {
                 match term {
                     Term::Terminal(t) => {
@@ -650,54 +1383,206 @@ fn calculate_first(grammar: &Grammar) -> HashMap<Nonterminal, HashSet<TerminalOr
     first
 }
 
+/// FOLLOW sets: terminals that can appear right after the nonterminal,
+/// `Finish` follows the fake axiom.
+fn calculate_follow(
+    grammar: &Grammar,
+    first: &HashMap<Nonterminal, HashSet<TerminalOrEmpty>>,
+) -> HashMap<Nonterminal, HashSet<TerminalOrFinish>> {
+    let mut follow: HashMap<Nonterminal, HashSet<TerminalOrFinish>> =
+        first.keys().map(|x| (x.clone(), HashSet::new())).collect();
+    let axiom = Nonterminal(String::from(GRAMMAR_AXIOM_NAME));
+    if let Some(set) = follow.get_mut(&axiom) {
+        set.insert(TerminalOrFinish::Finish);
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for rule in &grammar.rules {
+            // FOLLOW of the symbol after the current one, from right to left
+            let mut next: HashSet<TerminalOrFinish> = follow[&rule.left].clone();
+            for term in rule.right.iter().rev() {
+                match term {
+                    Term::Terminal(t) => {
+                        next = HashSet::from([TerminalOrFinish::Terminal(t.clone())]);
+                    }
+                    Term::Nonterminal(nterm) => {
+                        let set = follow.get_mut(nterm).expect("no follow set");
+                        let size = set.len();
+                        set.extend(next.iter().cloned());
+                        changed = changed || set.len() != size;
+                        let first_terminals = first[nterm].iter().filter_map(|x| match x {
+                            TerminalOrEmpty::Terminal(t) => {
+                                Some(TerminalOrFinish::Terminal(t.clone()))
+                            }
+                            TerminalOrEmpty::Empty => None,
+                        });
+                        if first[nterm].contains(&TerminalOrEmpty::Empty) {
+                            next.extend(first_terminals);
+                        } else {
+                            next = first_terminals.collect();
+                        }
+                    }
+                }
+            }
+        }
+    }
+    follow
+}
+
+/// Adds a rule of the fake axiom for the axiom and each other entry, the first one is
+/// of the axiom.
 fn add_fake_axiom(grammar: &mut Grammar) {
-    grammar.rules.push(Rule {
-        left: Nonterminal(String::from(GRAMMAR_AXIOM_NAME)),
-        right: vec![Term::Nonterminal(grammar.axiom.clone())],
-    });
+    grammar.entries.retain(|x| *x != grammar.axiom);
+    let mut seen = HashSet::new();
+    grammar.entries.retain(|x| seen.insert(x.clone()));
+    for entry in [&grammar.axiom].into_iter().chain(&grammar.entries) {
+        grammar.rules.push(Rule {
+            left: Nonterminal(String::from(GRAMMAR_AXIOM_NAME)),
+            right: vec![Term::Nonterminal(entry.clone())],
+        });
+    }
+}
+
+/// Tag of the token the lexer returns for a literal without the closing quote, the
+/// parser of grammars has no such terminal.
+const UNTERMINATED_TAG: &str = "unterminated";
+
+/// Tokens of a grammar file up to `Finish`, an unterminated literal is an error.
+fn get_grammar_tokens(
+    input: &str,
+    file: usize,
+) -> Result<Vec<Token<TokenAttribute>>, GeneratorError> {
+    let tokens = Lexer::for_file(input, file).get_tokens();
+    for token in &tokens {
+        if matches!(&token.tag, TerminalOrFinish::Terminal(t) if t.0 == UNTERMINATED_TAG) {
+            return Err(GeneratorError::UnterminatedLiteral(
+                token.attribute.fragment.clone(),
+            ));
+        }
+    }
+    Ok(tokens)
 }
 
+#[derive(Clone)]
 pub struct Lexer<'a> {
+    /// index of the grammar file in the sources
+    file: usize,
     cur: Coord,
     input: &'a str,
+    allows_operator: bool,
+    is_finished: bool,
+    /// nesting of `<` and `>`, declarations are at depth 1
+    depth: usize,
+    /// the next token is the first one of a declaration, where keywords are recognized
+    is_head: bool,
+    /// the current declaration is a rule, its alternatives may end with `prec`
+    is_rule: bool,
+}
+
+/// Tokens of the input up to `Finish` inclusive.
+impl Iterator for Lexer<'_> {
+    type Item = Token<TokenAttribute>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+        let token = self.get_next_token();
+        self.is_finished = token.tag == TerminalOrFinish::Finish;
+        Some(token)
+    }
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
+        Lexer::for_file(input, 0)
+    }
+
+    /// Lexer of the grammar file with the index `file` in the sources.
+    pub fn for_file(input: &'a str, file: usize) -> Self {
         Lexer {
+            file,
             cur: Coord {
                 line: 1,
                 column: 1,
                 index: 0,
             },
             input,
+            allows_operator: false,
+            is_finished: false,
+            depth: 0,
+            is_head: false,
+            is_rule: false,
         }
     }
 
     pub fn get_tokens(&mut self) -> Vec<Token<TokenAttribute>> {
-        let mut res = Vec::new();
-        loop {
-            let token = self.get_next_token();
-            let is_finish = token.tag == TerminalOrFinish::Finish;
-            res.push(token);
-            if is_finish {
-                break;
-            }
-        }
-        res
+        self.collect()
     }
 
     fn get_next_token(&mut self) -> Token<TokenAttribute> {
+        let token = self.get_next_token_rec();
+        let tag = match &token.tag {
+            TerminalOrFinish::Terminal(t) => t.0.as_str(),
+            TerminalOrFinish::Finish => "",
+        };
+        self.allows_operator = ["nterm", "str", "close", "op"].contains(&tag);
+        if self.is_head {
+            self.is_rule = tag == "nterm";
+        }
+        self.is_head = tag == "open" && self.depth == 0;
+        match tag {
+            "open" => self.depth += 1,
+            "close" => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+        token
+    }
+
+    /// `prec` is a keyword only at the end of an alternative, followed by a terminal.
+    fn is_prec_annotation(&self) -> bool {
+        if !self.is_rule || self.depth != 2 {
+            return false;
+        }
+        let mut lexer = self.clone();
+        lexer.is_rule = false;
+        let tags: Vec<TerminalOrFinish> = (0..2).map(|_| lexer.get_next_token_rec().tag).collect();
+        let is_tag = |i: usize, names: &[&str]| matches!(&tags[i], TerminalOrFinish::Terminal(t) if names.contains(&t.0.as_str()));
+        is_tag(0, &["term", "str"]) && is_tag(1, &["close", "code"])
+    }
+
+    fn get_next_token_rec(&mut self) -> Token<TokenAttribute> {
+        let begin = self.cur.clone();
+        if self.allows_operator {
+            if let Some(ch) = self.peek().filter(|c| is_operator(*c)) {
+                self.next();
+                return Token {
+                    tag: TerminalOrFinish::Terminal(Terminal(String::from("op"))),
+                    attribute: TokenAttribute {
+                        fragment: Fragment {
+                            file: self.file,
+                            begin,
+                            end: self.cur.clone(),
+                        },
+                        domain_attribute: TokenDomainAttribute::Operator(ch),
+                    },
+                };
+            }
+        }
         self.skip_spaces();
         let begin = self.cur.clone();
         match self.peek() {
             Some(ch) => {
                 if ch.is_uppercase() {
-                    let res = self.read_while(|c| !c.is_whitespace() && c != '<' && c != '>');
+                    let res = self.read_while(|c| {
+                        !c.is_whitespace() && c != '<' && c != '>' && !is_operator(c)
+                    });
                     Token {
                         tag: TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
                         attribute: TokenAttribute {
                             fragment: Fragment {
+                                file: self.file,
                                 begin,
                                 end: self.cur.clone(),
                             },
@@ -710,6 +1595,7 @@ impl<'a> Lexer<'a> {
                         tag: TerminalOrFinish::Terminal(Terminal(String::from("open"))),
                         attribute: TokenAttribute {
                             fragment: Fragment {
+                                file: self.file,
                                 begin,
                                 end: self.cur.clone(),
                             },
@@ -722,27 +1608,93 @@ impl<'a> Lexer<'a> {
                         tag: TerminalOrFinish::Terminal(Terminal(String::from("close"))),
                         attribute: TokenAttribute {
                             fragment: Fragment {
+                                file: self.file,
                                 begin,
                                 end: self.cur.clone(),
                             },
                             domain_attribute: TokenDomainAttribute::None,
                         },
                     }
-                } else if ch == '\'' {
+                } else if ch == '"' || ch == '\'' {
+                    self.next();
+                    let quote = self.cur.clone();
+                    let Some(res) = self.read_string_literal(ch) else {
+                        return Token {
+                            tag: TerminalOrFinish::Terminal(Terminal(String::from(
+                                UNTERMINATED_TAG,
+                            ))),
+                            attribute: TokenAttribute {
+                                fragment: Fragment {
+                                    file: self.file,
+                                    begin,
+                                    end: quote,
+                                },
+                                domain_attribute: TokenDomainAttribute::None,
+                            },
+                        };
+                    };
+                    Token {
+                        tag: TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+                        attribute: TokenAttribute {
+                            fragment: Fragment {
+                                file: self.file,
+                                begin,
+                                end: self.cur.clone(),
+                            },
+                            domain_attribute: TokenDomainAttribute::Literal(res),
+                        },
+                    }
+                } else if ch == '%' && self.peek_nth(1) == Some('{') {
+                    self.next();
+                    self.next();
+                    let res = self.read_code_block();
+                    Token {
+                        tag: TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+                        attribute: TokenAttribute {
+                            fragment: Fragment {
+                                file: self.file,
+                                begin,
+                                end: self.cur.clone(),
+                            },
+                            domain_attribute: TokenDomainAttribute::Literal(res),
+                        },
+                    }
+                } else if ch == '/' && self.peek_nth(1) == Some('/') {
                     self.read_while(|c| c != '\n');
                     self.next();
-                    self.get_next_token()
+                    self.get_next_token_rec()
                 } else {
                     let res = self.read_while(|c| !c.is_whitespace() && c != '<' && c != '>');
-                    let tag_name = if res == "axiom" { "ax" } else { "term" };
+                    let (tag_name, domain_attribute) = match res.as_str() {
+                        "axiom" => ("ax", TokenDomainAttribute::None),
+                        "prec" if self.is_prec_annotation() => ("pr", TokenDomainAttribute::None),
+                        "token" if self.is_head => ("tok", TokenDomainAttribute::None),
+                        "skip" if self.is_head => ("sk", TokenDomainAttribute::None),
+                        "attribute" if self.is_head => ("atr", TokenDomainAttribute::None),
+                        "import" if self.is_head => ("imp", TokenDomainAttribute::None),
+                        "left" if self.is_head => (
+                            "assoc",
+                            TokenDomainAttribute::Associativity(Associativity::Left),
+                        ),
+                        "right" if self.is_head => (
+                            "assoc",
+                            TokenDomainAttribute::Associativity(Associativity::Right),
+                        ),
+                        "nonassoc" if self.is_head => (
+                            "assoc",
+                            TokenDomainAttribute::Associativity(Associativity::NonAssoc),
+                        ),
+                        _ => ("term", TokenDomainAttribute::Terminal(res)),
+                    };
                     Token {
                         tag: TerminalOrFinish::Terminal(Terminal(String::from(tag_name))),
                         attribute: TokenAttribute {
                             fragment: Fragment {
+                                file: self.file,
                                 begin,
                                 end: self.cur.clone(),
                             },
-                            domain_attribute: TokenDomainAttribute::Terminal(res),
+                            domain_attribute,
                         },
                     }
                 }
@@ -751,6 +1703,7 @@ impl<'a> Lexer<'a> {
                 tag: TerminalOrFinish::Finish,
                 attribute: TokenAttribute {
                     fragment: Fragment {
+                        file: self.file,
                         begin: self.cur.clone(),
                         end: self.cur.clone(),
                     },
@@ -775,6 +1728,44 @@ impl<'a> Lexer<'a> {
         res
     }
 
+    /// Reads the rest of a string quoted by `quote`, the escaped quote is unescaped and
+    /// other backslash sequences are kept as is for the regex parser. Returns `None` if
+    /// the input ends before the closing quote.
+    fn read_string_literal(&mut self, quote: char) -> Option<String> {
+        let mut res = String::new();
+        while let Some(c) = self.peek() {
+            self.next();
+            match c {
+                c if c == quote => return Some(res),
+                '\\' => {
+                    if let Some(escaped) = self.peek() {
+                        if escaped != quote {
+                            res.push('\\');
+                        }
+                        res.push(escaped);
+                        self.next();
+                    }
+                }
+                c => res.push(c),
+            }
+        }
+        None
+    }
+
+    /// Reads the rest of a `%{ ... %}` block, the code is returned without the braces.
+    fn read_code_block(&mut self) -> String {
+        let mut res = String::new();
+        while let Some(c) = self.peek() {
+            self.next();
+            if c == '%' && self.peek() == Some('}') {
+                self.next();
+                break;
+            }
+            res.push(c);
+        }
+        String::from(res.trim())
+    }
+
     fn skip_spaces(&mut self) {
         while self.is_space() {
             self.next()
@@ -789,7 +1780,11 @@ impl<'a> Lexer<'a> {
     }
 
     fn peek(&self) -> Option<char> {
-        self.input.chars().nth(self.cur.index as usize)
+        self.peek_nth(0)
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.input.chars().nth(self.cur.index as usize + n)
     }
 
     fn next(&mut self) {
@@ -805,6 +1800,10 @@ impl<'a> Lexer<'a> {
     }
 }
 
+fn is_operator(c: char) -> bool {
+    c == '?' || c == '*' || c == '+'
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct TokenAttribute {
     fragment: Fragment,
@@ -815,6 +1814,9 @@ pub struct TokenAttribute {
 enum TokenDomainAttribute {
     Nonterminal(String),
     Terminal(String),
+    Associativity(Associativity),
+    Operator(char),
+    Literal(String),
     None,
 }
 
@@ -825,96 +1827,501 @@ impl TokenDomainAttribute {
         }
         None
     }
+
+    /// Name of a plain or quoted terminal.
+    fn as_terminal(&self) -> Option<String> {
+        match self {
+            TokenDomainAttribute::Terminal(s) => Some(s.clone()),
+            TokenDomainAttribute::Literal(s) => Some(unescape(s)),
+            _ => None,
+        }
+    }
 }
 
+/// Grammar file read by the generator, fragments refer to it by its index in the sources.
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub name: String,
+    pub text: String,
+}
+
+impl Source {
+    pub fn new(name: &str, text: &str) -> Source {
+        Source {
+            name: String::from(name),
+            text: String::from(text),
+        }
+    }
+}
+
+/// Part of the grammar source between two positions, the end is exclusive.
 #[derive(PartialEq, Eq, Debug, Clone)]
-struct Fragment {
-    begin: Coord,
-    end: Coord,
+pub struct Fragment {
+    /// index of the grammar file in the sources, 0 is the file with the axiom
+    pub file: usize,
+    pub begin: Coord,
+    pub end: Coord,
+}
+
+impl Fragment {
+    fn join(begin: &Fragment, end: &Fragment) -> Fragment {
+        Fragment {
+            file: begin.file,
+            begin: begin.begin.clone(),
+            end: end.end.clone(),
+        }
+    }
 }
 
+/// Position in the grammar source, lines and columns are numbered from 1 and columns
+/// and indices count chars.
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct Coord {
-    line: i32,
-    column: i32,
-    index: i32,
+    pub line: i32,
+    pub column: i32,
+    pub index: i32,
+}
+
+/// First definitions of nonterminal types, precedences and the attribute type, and
+/// imports to read. Rules of a nonterminal may be declared in several places of one file.
+#[derive(Default)]
+struct Definitions {
+    first: HashMap<String, Fragment>,
+    imports: VecDeque<(String, Fragment)>,
+}
+
+impl Definitions {
+    fn define(&mut self, name: String, fragment: &Fragment) -> Result<(), GeneratorError> {
+        if let Some(previous) = self.first.get(&name) {
+            return Err(GeneratorError::DuplicateDefinition {
+                name,
+                fragment: Some(fragment.clone()),
+                previous: Some(previous.clone()),
+            });
+        }
+        self.first.insert(name, fragment.clone());
+        Ok(())
+    }
+
+    /// Rules of a nonterminal from different files are not merged.
+    fn declare_rules(&mut self, nterm: &str, fragment: &Fragment) -> Result<(), GeneratorError> {
+        let name = format!("Nonterminal {}", nterm);
+        match self.first.get(&name) {
+            Some(previous) if previous.file == fragment.file => Ok(()),
+            _ => self.define(name, fragment),
+        }
+    }
 }
 
-pub fn get_grammar_from_tree(root: &ParseTree<TokenAttribute>) -> Result<Grammar, GeneratorError> {
+pub fn get_grammar_from_tree(
+    root: &ParseTree<TokenAttribute>,
+    sources: &mut Vec<Source>,
+) -> Result<Grammar, GeneratorError> {
     let (_, root_children) = root.as_internal().unwrap();
     let (_, children) = root_children[0].as_internal().unwrap();
     let t = children[3].as_leaf().unwrap();
     let axiom_name = t.attribute.domain_attribute.as_nonterminal().unwrap();
     let axiom = Nonterminal(axiom_name);
-    let rules = get_rules_from_tree(&root_children[1]);
-    let mut grammar = Grammar { axiom, rules };
+    let mut entries = vec![(axiom.clone(), t.attribute.fragment.clone())];
+    entries.extend(get_nonterminals_from_tree(&children[5]));
+    let mut grammar = Grammar {
+        axiom,
+        entries: entries[1..].iter().map(|(x, _)| x.clone()).collect(),
+        rules: Vec::new(),
+        precedence: Precedence::default(),
+        tokens: Vec::new(),
+        semantics: Semantics::default(),
+        spans: Vec::new(),
+        reserved: Vec::new(),
+    };
+    let mut definitions = Definitions::default();
+    add_declarations_from_tree(&mut grammar, &root_children[1], &mut definitions)?;
+    add_imports(&mut grammar, &mut definitions, sources)?;
     add_fake_axiom(&mut grammar);
+    // rules of the fake axiom point to the entries in the `axiom` declaration
+    for rule in &grammar.rules[grammar.spans.len()..] {
+        let fragment = entries
+            .iter()
+            .find(|(x, _)| rule.right[0] == Term::Nonterminal(x.clone()))
+            .map(|(_, fragment)| fragment.clone())
+            .unwrap();
+        grammar.spans.push(RuleSpan {
+            rule: fragment.clone(),
+            right: vec![fragment],
+            prec: None,
+        });
+    }
     validate_grammar(&grammar)?;
     Ok(grammar)
 }
 
-fn get_rules_from_tree(root: &ParseTree<TokenAttribute>) -> Vec<Rule> {
+/// Reads the imported files with the meta grammar entry `R`, so they contain only
+/// declarations. A file imported several times, or importing the main file, is read once.
+fn add_imports(
+    grammar: &mut Grammar,
+    definitions: &mut Definitions,
+    sources: &mut Vec<Source>,
+) -> Result<(), GeneratorError> {
+    let mut loaded: Vec<PathBuf> = sources
+        .iter()
+        .filter_map(|x| fs::canonicalize(&x.name).ok())
+        .collect();
+    let tables = parser::get_parse_tables();
+    while let Some((name, fragment)) = definitions.imports.pop_front() {
+        let importer = Path::new(&sources[fragment.file].name);
+        let path = importer.parent().unwrap_or(Path::new("")).join(&name);
+        let import_error = |err: std::io::Error| GeneratorError::ImportError {
+            path: name.clone(),
+            message: err.to_string(),
+            fragment: Some(fragment.clone()),
+        };
+        let canonical = fs::canonicalize(&path).map_err(import_error)?;
+        if loaded.contains(&canonical) {
+            continue;
+        }
+        let text = fs::read_to_string(&path).map_err(import_error)?;
+        loaded.push(canonical);
+        let file = sources.len();
+        sources.push(Source {
+            name: path.to_string_lossy().into_owned(),
+            text,
+        });
+        let tokens = get_grammar_tokens(&sources[file].text, file)?;
+        let tree = ParseTree::from_tables_and_tokens_for(&tables, "R", &tokens)?;
+        add_declarations_from_tree(grammar, &tree, definitions)?;
+    }
+    Ok(())
+}
+
+fn add_declarations_from_tree(
+    grammar: &mut Grammar,
+    root: &ParseTree<TokenAttribute>,
+    definitions: &mut Definitions,
+) -> Result<(), GeneratorError> {
+    let (_, children) = root.as_internal().unwrap();
+    if children.is_empty() {
+        return Ok(());
+    }
+    let (_, declaration) = children[0].as_internal().unwrap();
+    let t = declaration[1].as_leaf().unwrap();
+    match &t.attribute.domain_attribute {
+        TokenDomainAttribute::Nonterminal(name) => {
+            let left = Nonterminal(name.clone());
+            definitions.declare_rules(name, &t.attribute.fragment)?;
+            if let Some(code) = get_code_from_tree(&declaration[2]) {
+                definitions.define(
+                    format!("Type of nonterminal {}", name),
+                    &t.attribute.fragment,
+                )?;
+                grammar.semantics.types.insert(left.clone(), code);
+            }
+            add_subrules_from_tree(grammar, &left, &declaration[3]);
+        }
+        TokenDomainAttribute::Associativity(associativity) => {
+            let mut terminals = Vec::new();
+            for (term, fragment) in get_terminals_from_tree(&declaration[3]) {
+                definitions.define(format!("Precedence of terminal {}", term.0), &fragment)?;
+                terminals.push(term);
+            }
+            grammar.precedence.add_level(*associativity, terminals);
+        }
+        TokenDomainAttribute::None if t.tag.to_string() == "atr" => {
+            definitions.define(String::from("Attribute type"), &t.attribute.fragment)?;
+            grammar.semantics.attribute = get_code_from_tree(&declaration[2]);
+        }
+        TokenDomainAttribute::None if t.tag.to_string() == "imp" => {
+            let path = declaration[2].as_leaf().unwrap();
+            let TokenDomainAttribute::Literal(name) = &path.attribute.domain_attribute else {
+                panic!("must be string literal");
+            };
+            let fragment = path.attribute.fragment.clone();
+            definitions.imports.push_back((unescape(name), fragment));
+        }
+        TokenDomainAttribute::None => {
+            let (terminal, regex) = match declaration.len() {
+                5 => (declaration[2].as_leaf(), &declaration[3]),
+                _ => (None, &declaration[2]),
+            };
+            let terminal = terminal.map(|t| {
+                let name = t.attribute.domain_attribute.as_terminal().unwrap();
+                Terminal(name)
+            });
+            let regex = regex.as_leaf().unwrap();
+            let TokenDomainAttribute::Literal(literal) = &regex.attribute.domain_attribute else {
+                panic!("must be string literal");
+            };
+            grammar.tokens.push(TokenDeclaration {
+                terminal,
+                regex: literal.clone(),
+                fragment: regex.attribute.fragment.clone(),
+            });
+        }
+        _ => panic!("must be rule, precedence, token or import declaration"),
+    }
+    add_declarations_from_tree(grammar, &children[1], definitions)
+}
+
+fn get_nonterminals_from_tree(root: &ParseTree<TokenAttribute>) -> Vec<(Nonterminal, Fragment)> {
     let (_, children) = root.as_internal().unwrap();
     if children.is_empty() {
         return Vec::new();
     }
-    let right = get_rules_from_tree(&children[1]);
-    let (_, children) = &children[0].as_internal().unwrap();
     let t = children[1].as_leaf().unwrap();
     let name = t.attribute.domain_attribute.as_nonterminal().unwrap();
-    let left = Nonterminal(name);
-    let mut rules = get_subrules_from_tree(&left, &children[2]);
-    rules.extend(right);
-    rules
+    let mut res = vec![(Nonterminal(name), t.attribute.fragment.clone())];
+    res.extend(get_nonterminals_from_tree(&children[3]));
+    res
 }
 
-fn get_subrules_from_tree(left: &Nonterminal, root: &ParseTree<TokenAttribute>) -> Vec<Rule> {
+fn get_terminals_from_tree(root: &ParseTree<TokenAttribute>) -> Vec<(Terminal, Fragment)> {
     let (_, children) = root.as_internal().unwrap();
     if children.is_empty() {
         return Vec::new();
     }
-    let right = get_subrules_from_tree(left, &children[3]);
-    let terms = get_terms_from_subtree(&children[1]);
-    let mut res = vec![Rule {
+    let t = children[0].as_leaf().unwrap();
+    let name = t.attribute.domain_attribute.as_terminal().unwrap();
+    let mut res = vec![(Terminal(name), t.attribute.fragment.clone())];
+    res.extend(get_terminals_from_tree(&children[1]));
+    res
+}
+
+fn add_subrules_from_tree(
+    grammar: &mut Grammar,
+    left: &Nonterminal,
+    root: &ParseTree<TokenAttribute>,
+) {
+    let (_, children) = root.as_internal().unwrap();
+    if children.is_empty() {
+        return;
+    }
+    let (right, fragments) = get_terms_from_subtree(grammar, left, &children[1])
+        .into_iter()
+        .unzip();
+    let rule = Rule {
         left: left.clone(),
-        right: terms,
-    }];
-    res.extend(right);
+        right,
+    };
+    let (_, prec) = children[2].as_internal().unwrap();
+    let prec = prec.get(1).map(|x| x.as_leaf().unwrap());
+    if let Some(t) = prec {
+        let name = t.attribute.domain_attribute.as_terminal().unwrap();
+        grammar
+            .precedence
+            .rules
+            .insert(rule.clone(), Terminal(name));
+    }
+    if let Some(code) = get_code_from_tree(&children[3]) {
+        grammar.semantics.actions.insert(rule.clone(), code);
+    }
+    let fragment = Fragment::join(
+        get_leaf_fragment(&children[0]),
+        get_leaf_fragment(&children[4]),
+    );
+    add_rule(grammar, rule, fragment, fragments);
+    // precedence levels may be declared later, they are checked in `validate_grammar`
+    if let Some(span) = grammar.spans.last_mut() {
+        span.prec = prec.map(|t| t.attribute.fragment.clone());
+    }
+    add_subrules_from_tree(grammar, left, &children[5]);
+}
+
+fn add_rule(grammar: &mut Grammar, rule: Rule, fragment: Fragment, right: Vec<Fragment>) {
+    grammar.rules.push(rule);
+    grammar.spans.push(RuleSpan {
+        rule: fragment,
+        right,
+        prec: None,
+    });
+}
+
+fn get_leaf_fragment(root: &ParseTree<TokenAttribute>) -> &Fragment {
+    &root.as_leaf().unwrap().attribute.fragment
+}
+
+/// Returns the code of a `code` token or of a node with an optional `code` child.
+fn get_code_from_tree(root: &ParseTree<TokenAttribute>) -> Option<String> {
+    let t = match root {
+        ParseTree::Leaf(t) => t,
+        ParseTree::Internal(_, children) => children.first()?.as_leaf().unwrap(),
+        ParseTree::Error(_) => unreachable!(),
+    };
+    match &t.attribute.domain_attribute {
+        TokenDomainAttribute::Literal(code) => Some(code.clone()),
+        _ => panic!("must be code block"),
+    }
+}
+
+/// Unescapes the name of a quoted terminal, unknown sequences are kept as is.
+fn unescape(name: &str) -> String {
+    let mut res = String::new();
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => res.push('\n'),
+            Some('t') => res.push('\t'),
+            Some('r') => res.push('\r'),
+            Some(c @ ('\\' | '\'' | '"')) => res.push(c),
+            Some(c) => {
+                res.push('\\');
+                res.push(c);
+            }
+            None => res.push('\\'),
+        }
+    }
     res
 }
 
-fn get_terms_from_subtree(root: &ParseTree<TokenAttribute>) -> Vec<Term> {
+/// Returns the symbols of an alternative with the fragments of the items they come from.
+fn get_terms_from_subtree(
+    grammar: &mut Grammar,
+    owner: &Nonterminal,
+    root: &ParseTree<TokenAttribute>,
+) -> Vec<(Term, Fragment)> {
     let (_, children) = root.as_internal().unwrap();
     if children.is_empty() {
         return Vec::new();
     }
-    let right = get_terms_from_subtree(&children[1]);
-    let t = children[0].as_leaf().unwrap();
-    match t.attribute.domain_attribute.clone() {
-        TokenDomainAttribute::Nonterminal(nterm) => {
-            let mut res = vec![Term::Nonterminal(Nonterminal(nterm))];
-            res.extend(right);
-            res
+    let mut res = get_terms_from_item(grammar, owner, &children[0]);
+    res.extend(get_terms_from_subtree(grammar, owner, &children[1]));
+    res
+}
+
+/// Desugars a single item of an alternative. A group without an operator is inlined,
+/// otherwise helper nonterminals named `Owner<k>`, `Owner<k>?`, `Owner<k>*` and `Owner<k>+`
+/// are added. Operators apply only to nonterminals, quoted terminals and groups, so `<a>*`
+/// repeats a terminal.
+fn get_terms_from_item(
+    grammar: &mut Grammar,
+    owner: &Nonterminal,
+    root: &ParseTree<TokenAttribute>,
+) -> Vec<(Term, Fragment)> {
+    let (_, children) = root.as_internal().unwrap();
+    let operator = children.last().unwrap();
+    let (_, operator) = operator.as_internal().unwrap();
+    let last = match operator.first() {
+        Some(operator) => operator,
+        None => &children[children.len() - 2],
+    };
+    let fragment = Fragment::join(get_leaf_fragment(&children[0]), get_leaf_fragment(last));
+    let operator = operator.first().map(|x| {
+        let t = x.as_leaf().unwrap();
+        match t.attribute.domain_attribute {
+            TokenDomainAttribute::Operator(c) => c,
+            _ => panic!("must be operator"),
         }
+    });
+    let term = match children[0]
+        .as_leaf()
+        .unwrap()
+        .attribute
+        .domain_attribute
+        .clone()
+    {
+        TokenDomainAttribute::Nonterminal(nterm) => Term::Nonterminal(Nonterminal(nterm)),
         TokenDomainAttribute::Terminal(term) => {
-            let mut res = vec![Term::Terminal(Terminal(term))];
-            res.extend(right);
-            res
+            if RESERVED_WORDS.contains(&term.as_str()) {
+                let fragment = get_leaf_fragment(&children[0]).clone();
+                grammar.reserved.push((Terminal(term.clone()), fragment));
+            }
+            Term::Terminal(Terminal(term))
+        }
+        TokenDomainAttribute::Literal(term) => Term::Terminal(Terminal(unescape(&term))),
+        TokenDomainAttribute::None => {
+            let right = get_terms_from_subtree(grammar, owner, &children[1]);
+            if operator.is_none() {
+                return right;
+            }
+            if right.len() == 1 {
+                right[0].0.clone()
+            } else {
+                let group = Nonterminal(get_fresh_name(grammar, owner));
+                let (right, fragments) = right.into_iter().unzip();
+                let rule = Rule {
+                    left: group.clone(),
+                    right,
+                };
+                add_rule(grammar, rule, fragment.clone(), fragments);
+                Term::Nonterminal(group)
+            }
+        }
+        _ => panic!("must be terminal, nonterminal or group"),
+    };
+    let Some(operator) = operator else {
+        return vec![(term, fragment)];
+    };
+    let name = get_fresh_name(grammar, owner);
+    let nterm = match operator {
+        '?' => Nonterminal(format!("{}?", name)),
+        _ => Nonterminal(format!("{}*", name)),
+    };
+    let rule = Rule {
+        left: nterm.clone(),
+        right: Vec::new(),
+    };
+    add_rule(grammar, rule, fragment.clone(), Vec::new());
+    let mut right = vec![term.clone()];
+    if operator != '?' {
+        right.push(Term::Nonterminal(nterm.clone()));
+    }
+    let fragments = vec![fragment.clone(); right.len()];
+    let rule = Rule {
+        left: nterm.clone(),
+        right,
+    };
+    add_rule(grammar, rule, fragment.clone(), fragments);
+    if operator != '+' {
+        return vec![(Term::Nonterminal(nterm), fragment)];
+    }
+    let head = Nonterminal(format!("{}+", name));
+    let rule = Rule {
+        left: head.clone(),
+        right: vec![term, Term::Nonterminal(nterm)],
+    };
+    add_rule(grammar, rule, fragment.clone(), vec![fragment.clone(); 2]);
+    vec![(Term::Nonterminal(head), fragment)]
+}
+
+fn get_fresh_name(grammar: &Grammar, owner: &Nonterminal) -> String {
+    let mut k = 1;
+    loop {
+        let name = format!("{}<{}>", owner.0, k);
+        if !grammar.rules.iter().any(|x| x.left.0.starts_with(&name)) {
+            return name;
         }
-        _ => panic!("must be terminal or nonterminal"),
+        k += 1;
     }
 }
 
 fn validate_grammar(grammar: &Grammar) -> Result<(), GeneratorError> {
     let left: HashSet<Nonterminal> = grammar.rules.iter().map(|x| x.left.clone()).collect();
-    for rule in &grammar.rules {
-        for term in &rule.right {
+    for (i, rule) in grammar.rules.iter().enumerate() {
+        for (j, term) in rule.right.iter().enumerate() {
             if let Term::Nonterminal(nterm) = term {
                 if !left.contains(nterm) {
-                    return Err(GeneratorError::UndeclaredNonterminal(nterm.clone()));
+                    let fragment = grammar.spans.get(i).map(|x| x.right[j].clone());
+                    return Err(GeneratorError::UndeclaredNonterminal(
+                        nterm.clone(),
+                        fragment,
+                    ));
                 }
             }
         }
     }
+    for (rule, span) in grammar.rules.iter().zip(&grammar.spans) {
+        let Some(fragment) = &span.prec else {
+            continue;
+        };
+        let terminal = &grammar.precedence.rules[rule];
+        if !grammar.precedence.terminals.contains_key(terminal) {
+            return Err(GeneratorError::UndeclaredPrecedence {
+                terminal: terminal.clone(),
+                fragment: Some(fragment.clone()),
+            });
+        }
+    }
     Ok(())
 }
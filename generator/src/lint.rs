@@ -0,0 +1,256 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use crate::parser::*;
+use crate::{
+    calculate_first, render_message, Fragment, Grammar, Source, TerminalOrEmpty,
+    GRAMMAR_AXIOM_NAME, RESERVED_WORDS,
+};
+
+/// Kind of a suspicious construction of a grammar that still gives valid tables.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Lint {
+    /// Nonterminal that can't be derived from the axiom or other entries.
+    Unreachable,
+    /// Nonterminal that derives no string of terminals.
+    Unproductive,
+    /// Alternative declared twice for the same nonterminal.
+    DuplicateAlternative,
+    /// Rule that only derives its own left side, like `A -> A`.
+    Cycle,
+    /// Terminal named like a keyword of the grammar format without quotes.
+    ReservedTerminal,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 5] = [
+        Lint::Unreachable,
+        Lint::Unproductive,
+        Lint::DuplicateAlternative,
+        Lint::Cycle,
+        Lint::ReservedTerminal,
+    ];
+
+    /// Name of the lint for command-line options.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::Unreachable => "unreachable",
+            Lint::Unproductive => "unproductive",
+            Lint::DuplicateAlternative => "duplicate",
+            Lint::Cycle => "cycle",
+            Lint::ReservedTerminal => "reserved",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|x| x.name() == name)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+#[derive(Debug, Clone)]
+pub struct LintWarning {
+    pub lint: Lint,
+    pub message: String,
+    pub fragment: Option<Fragment>,
+}
+
+impl LintWarning {
+    /// Formats the warning like `GeneratorError::render`.
+    pub fn render(&self, source: &str, file: &str) -> String {
+        self.render_sources(&[Source::new(file, source)])
+    }
+
+    pub fn render_sources(&self, sources: &[Source]) -> String {
+        render_message(&self.to_string(), self.fragment.as_ref(), sources)
+    }
+}
+
+impl Display for LintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}]", self.message, self.lint.name())
+    }
+}
+
+impl Grammar {
+    /// Finds unreachable and unproductive nonterminals, duplicate alternatives, cyclic
+    /// rules and terminals named like reserved words. Nonterminals generated for EBNF
+    /// operators are reported through the nonterminals they belong to.
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let mut res = Vec::new();
+        let nonterminals = self.get_nonterminals();
+        let reachable = self.get_reachable();
+        for (nterm, fragment) in &nonterminals {
+            if !reachable.contains(nterm) {
+                res.push(LintWarning {
+                    lint: Lint::Unreachable,
+                    message: format!("Nonterminal {} is unreachable from the axiom", nterm.0),
+                    fragment: fragment.clone(),
+                });
+            }
+        }
+        let productive = self.get_productive();
+        for (nterm, fragment) in &nonterminals {
+            if !productive.contains(nterm) {
+                res.push(LintWarning {
+                    lint: Lint::Unproductive,
+                    message: format!("Nonterminal {} derives no string of terminals", nterm.0),
+                    fragment: fragment.clone(),
+                });
+            }
+        }
+        for (i, rule) in self.rules.iter().enumerate() {
+            if self.rules[..i].contains(rule) {
+                res.push(LintWarning {
+                    lint: Lint::DuplicateAlternative,
+                    message: format!("Rule {} is declared more than once", rule),
+                    fragment: self.spans.get(i).map(|x| x.rule.clone()),
+                });
+            }
+        }
+        for i in self.get_cyclic_rules() {
+            res.push(LintWarning {
+                lint: Lint::Cycle,
+                message: format!("Rule {} derives its own left side", self.rules[i]),
+                fragment: self.spans.get(i).map(|x| x.rule.clone()),
+            });
+        }
+        // quoted terminals are the way to name a terminal like a keyword, so only bare
+        // words of parsed grammars and terminals of grammars built in code are reported
+        let mut reserved: Vec<(&Terminal, Option<Fragment>)> = Vec::new();
+        if self.spans.is_empty() {
+            for term in self.rules.iter().flat_map(|x| &x.right) {
+                if let Term::Terminal(t) = term {
+                    if RESERVED_WORDS.contains(&t.0.as_str()) {
+                        reserved.push((t, None));
+                    }
+                }
+            }
+        } else {
+            reserved.extend(self.reserved.iter().map(|(t, x)| (t, Some(x.clone()))));
+        }
+        let mut reported = HashSet::new();
+        for (t, fragment) in reserved {
+            if reported.insert(t) {
+                res.push(LintWarning {
+                    lint: Lint::ReservedTerminal,
+                    message: format!("Terminal {} is named like a reserved word", t.0),
+                    fragment,
+                });
+            }
+        }
+        res
+    }
+
+    /// Nonterminals written by the user with the fragments of their first rules.
+    fn get_nonterminals(&self) -> Vec<(&Nonterminal, Option<Fragment>)> {
+        let mut res: Vec<(&Nonterminal, Option<Fragment>)> = Vec::new();
+        for (i, rule) in self.rules.iter().enumerate() {
+            let nterm = &rule.left;
+            if nterm.0 == GRAMMAR_AXIOM_NAME || nterm.is_generated() {
+                continue;
+            }
+            if !res.iter().any(|(x, _)| *x == nterm) {
+                res.push((nterm, self.spans.get(i).map(|x| x.rule.clone())));
+            }
+        }
+        res
+    }
+
+    fn get_reachable(&self) -> HashSet<&Nonterminal> {
+        let mut by_left: HashMap<&Nonterminal, Vec<&Rule>> = HashMap::new();
+        for rule in &self.rules {
+            by_left.entry(&rule.left).or_default().push(rule);
+        }
+        let root = self.rules.iter().find(|x| x.left.0 == GRAMMAR_AXIOM_NAME);
+        let mut res = HashSet::new();
+        let mut stack: Vec<&Nonterminal> = root.map(|x| &x.left).into_iter().collect();
+        while let Some(cur) = stack.pop() {
+            if !res.insert(cur) {
+                continue;
+            }
+            for rule in by_left.get(cur).into_iter().flatten() {
+                for term in &rule.right {
+                    if let Term::Nonterminal(nterm) = term {
+                        stack.push(nterm);
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    fn get_productive(&self) -> HashSet<&Nonterminal> {
+        let mut res = HashSet::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for rule in &self.rules {
+                let is_productive = rule.right.iter().all(|x| match x {
+                    Term::Terminal(_) => true,
+                    Term::Nonterminal(nterm) => res.contains(nterm),
+                });
+                if is_productive && res.insert(&rule.left) {
+                    changed = true;
+                }
+            }
+        }
+        res
+    }
+
+    /// Indices of the rules `A -> B` whose other symbols are nullable and `B` derives `A`
+    /// the same way, so `A` derives itself.
+    fn get_cyclic_rules(&self) -> Vec<usize> {
+        let first = calculate_first(self);
+        let is_nullable = |term: &Term| match term {
+            Term::Terminal(_) => false,
+            Term::Nonterminal(nterm) => first[nterm].contains(&TerminalOrEmpty::Empty),
+        };
+        let mut units: Vec<HashSet<&Nonterminal>> = Vec::new();
+        let mut edges: HashMap<&Nonterminal, HashSet<&Nonterminal>> = HashMap::new();
+        for rule in &self.rules {
+            let mut targets = HashSet::new();
+            for (i, term) in rule.right.iter().enumerate() {
+                let Term::Nonterminal(nterm) = term else {
+                    continue;
+                };
+                let mut others = rule.right.iter().enumerate().filter(|(j, _)| *j != i);
+                if others.all(|(_, x)| is_nullable(x)) {
+                    targets.insert(nterm);
+                }
+            }
+            edges
+                .entry(&rule.left)
+                .or_default()
+                .extend(targets.iter().copied());
+            units.push(targets);
+        }
+        let derives = |from: &Nonterminal, to: &Nonterminal| {
+            let mut visited = HashSet::new();
+            let mut stack = vec![from];
+            while let Some(cur) = stack.pop() {
+                if cur == to {
+                    return true;
+                }
+                if visited.insert(cur) {
+                    stack.extend(edges.get(cur).into_iter().flatten().copied());
+                }
+            }
+            false
+        };
+        let mut res = Vec::new();
+        for (i, targets) in units.iter().enumerate() {
+            let left = &self.rules[i].left;
+            if targets.iter().any(|target| derives(target, left)) {
+                res.push(i);
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::parser::*;
+use crate::{
+    calculate_first, calculate_follow, terminal_or_finish_to_literal, with_runtime, GeneratorError,
+    Grammar, TerminalOrEmpty, GRAMMAR_AXIOM_NAME,
+};
+
+impl LL1ParseTables {
+    pub fn from_string(input: &str) -> Result<LL1ParseTables, GeneratorError> {
+        let grammar = Grammar::from_string(input)?;
+        LL1ParseTables::from_grammar(&grammar)
+    }
+
+    pub fn from_grammar(grammar: &Grammar) -> Result<LL1ParseTables, GeneratorError> {
+        let (tables, conflicts) = Self::from_grammar_with_conflicts(grammar);
+        match conflicts.into_iter().next() {
+            Some(conflict) => Err(conflict),
+            None => Ok(tables),
+        }
+    }
+
+    pub fn from_grammar_all_conflicts(grammar: &Grammar) -> Result<LL1ParseTables, GeneratorError> {
+        let (tables, conflicts) = Self::from_grammar_with_conflicts(grammar);
+        if conflicts.is_empty() {
+            Ok(tables)
+        } else {
+            Err(GeneratorError::Conflicts(conflicts))
+        }
+    }
+
+    /// A conflict is a lookahead in PREDICT sets of several rules of a nonterminal,
+    /// the table keeps the first of them.
+    fn from_grammar_with_conflicts(grammar: &Grammar) -> (LL1ParseTables, Vec<GeneratorError>) {
+        let first = calculate_first(grammar);
+        let follow = calculate_follow(grammar, &first);
+        let predict = calculate_predict(grammar, &first, &follow);
+        let mut rules: BTreeMap<(Nonterminal, TerminalOrFinish), Vec<Rule>> = BTreeMap::new();
+        for (rule, predict) in grammar.rules.iter().zip(predict) {
+            if rule.left.0 == GRAMMAR_AXIOM_NAME {
+                continue;
+            }
+            for lookahead in predict {
+                let key = (rule.left.clone(), lookahead);
+                rules.entry(key).or_default().push(rule.clone());
+            }
+        }
+        let mut conflicts = Vec::new();
+        let mut table = HashMap::new();
+        for ((nonterminal, lookahead), mut rules) in rules {
+            if rules.len() > 1 {
+                conflicts.push(GeneratorError::LL1Conflict {
+                    nonterminal: nonterminal.clone(),
+                    lookahead: lookahead.clone(),
+                    rules: rules.clone(),
+                    fragment: grammar.get_rule_fragment(&rules[1]),
+                });
+            }
+            table.insert((nonterminal, lookahead), rules.swap_remove(0));
+        }
+        let tables = LL1ParseTables {
+            axiom: grammar.axiom.clone(),
+            table,
+        };
+        (tables, conflicts)
+    }
+
+    /// Like `ParseTables::to_rust_source_with`, but `get_parse_tables()` returns
+    /// `LL1ParseTables`.
+    pub fn to_rust_source_with(&self, extra: &[String]) -> String {
+        with_runtime(&self.to_rust_function(), extra)
+    }
+
+    fn to_rust_function(&self) -> String {
+        let mut entries = String::new();
+        for ((nterm, lookahead), rule) in &self.table {
+            let entry = format!(
+                "((Nonterminal(String::from({:?})), {}), {}),\n",
+                nterm.0,
+                terminal_or_finish_to_literal(lookahead),
+                rule.to_literal()
+            );
+            entries += entry.as_ref();
+        }
+        format!(
+            r#"
+        pub fn get_parse_tables() -> LL1ParseTables {{
+            let table = [
+                {}
+            ].into_iter().collect();
+            LL1ParseTables {{
+                axiom: Nonterminal(String::from("{}")),
+                table,
+            }}
+        }}
+        "#,
+            entries, self.axiom.0
+        )
+    }
+}
+
+/// PREDICT sets of the rules: FIRST of the right side, with FOLLOW of the left
+/// side if the right side is nullable.
+pub(crate) fn calculate_predict(
+    grammar: &Grammar,
+    first: &HashMap<Nonterminal, HashSet<TerminalOrEmpty>>,
+    follow: &HashMap<Nonterminal, HashSet<TerminalOrFinish>>,
+) -> Vec<HashSet<TerminalOrFinish>> {
+    let mut res = Vec::new();
+    for rule in &grammar.rules {
+        let mut predict = HashSet::new();
+        let mut is_nullable = true;
+        for term in &rule.right {
+            match term {
+                Term::Terminal(t) => {
+                    predict.insert(TerminalOrFinish::Terminal(t.clone()));
+                    is_nullable = false;
+                }
+                Term::Nonterminal(nterm) => {
+                    predict.extend(first[nterm].iter().filter_map(|x| match x {
+                        TerminalOrEmpty::Terminal(t) => Some(TerminalOrFinish::Terminal(t.clone())),
+                        TerminalOrEmpty::Empty => None,
+                    }));
+                    is_nullable = first[nterm].contains(&TerminalOrEmpty::Empty);
+                }
+            }
+            if !is_nullable {
+                break;
+            }
+        }
+        if is_nullable {
+            predict.extend(follow[&rule.left].iter().cloned());
+        }
+        res.push(predict);
+    }
+    res
+}
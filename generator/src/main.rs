@@ -1,24 +1,85 @@
-use generator::parser::ParseTables;
+use generator::parser::{GLRParseTables, LL1ParseTables, LexerTables, ParseTables};
+use generator::{GeneratorError, Grammar, Lint, LintLevel, ParseTablesType, Source};
+use std::collections::HashMap;
 use std::env;
+use std::fs;
 use std::io;
 use std::process;
 
 struct Flags {
     help: bool,
-    clr: bool,
+    tables_type: ParseTablesType,
+    all_conflicts: bool,
+    typed_ast: bool,
+    dense: bool,
+    glr: bool,
+    ll1: bool,
+    file: Option<String>,
+    lints: HashMap<Lint, LintLevel>,
+}
+
+impl Flags {
+    fn file_name(&self) -> &str {
+        self.file.as_deref().unwrap_or("<stdin>")
+    }
+
+    /// Sets the level of the lint named in `--allow=`, `--warn=` or `--deny=`.
+    fn set_lint_level(&mut self, name: &str, level: LintLevel) {
+        if name == "all" {
+            for lint in Lint::ALL {
+                self.lints.insert(lint, level);
+            }
+        } else if let Some(lint) = Lint::from_name(name) {
+            self.lints.insert(lint, level);
+        } else {
+            eprintln!("Unknown lint: {}", name);
+            process::exit(1);
+        }
+    }
 }
 
 fn main() {
     let mut flags = Flags {
         help: false,
-        clr: false,
+        tables_type: ParseTablesType::LALR,
+        all_conflicts: false,
+        typed_ast: false,
+        dense: false,
+        glr: false,
+        ll1: false,
+        file: None,
+        lints: Lint::ALL.map(|x| (x, LintLevel::Warn)).into(),
     };
     let args: Vec<String> = env::args().collect();
     for arg in &args[1..] {
         if arg == "--clr" {
-            flags.clr = true;
+            flags.tables_type = ParseTablesType::LR1;
+        } else if arg == "--minimal-lr1" {
+            flags.tables_type = ParseTablesType::MinimalLR1;
+        } else if arg == "--slr" {
+            flags.tables_type = ParseTablesType::SLR;
+        } else if arg == "--lr0" {
+            flags.tables_type = ParseTablesType::LR0;
+        } else if arg == "--all-conflicts" {
+            flags.all_conflicts = true;
+        } else if arg == "--typed-ast" {
+            flags.typed_ast = true;
+        } else if arg == "--dense" {
+            flags.dense = true;
+        } else if arg == "--glr" {
+            flags.glr = true;
+        } else if arg == "--ll1" {
+            flags.ll1 = true;
         } else if arg == "--help" {
             flags.help = true;
+        } else if let Some(name) = arg.strip_prefix("--allow=") {
+            flags.set_lint_level(name, LintLevel::Allow);
+        } else if let Some(name) = arg.strip_prefix("--warn=") {
+            flags.set_lint_level(name, LintLevel::Warn);
+        } else if let Some(name) = arg.strip_prefix("--deny=") {
+            flags.set_lint_level(name, LintLevel::Deny);
+        } else if !arg.starts_with("--") && flags.file.is_none() {
+            flags.file = Some(arg.clone());
         } else {
             eprintln!("Unknown flag: {}", arg);
             process::exit(1);
@@ -27,33 +88,136 @@ fn main() {
     if flags.help {
         println!(
             "Usage: 
-  compgen [options]
+  compgen [options] [grammar file]
+
+The grammar is read from stdin if no file is given.
 
 OPTIONS:
-  --clr  generate CLR tables instead of LALR
-  --help show list of command-line options"
+  --clr           generate CLR tables instead of LALR
+  --minimal-lr1   split LALR states only where merging causes conflicts
+  --slr           generate SLR(1) tables, reduce on FOLLOW sets
+  --lr0           generate LR(0) tables, reduce on any lookahead
+  --all-conflicts report all table conflicts instead of the first one
+  --typed-ast     generate AST enums and conversions from parse trees
+  --dense         emit tables as static arrays with integer symbol ids
+  --glr           keep all actions of conflicts for the GLR parser
+  --ll1           generate LL(1) table for the top-down parser
+  --allow=LINT    do not report the lint
+  --warn=LINT     report the lint as a warning (default)
+  --deny=LINT     report the lint as an error and stop
+  --help          show list of command-line options
+
+LINTS:
+  unreachable     nonterminals not derivable from the axiom
+  unproductive    nonterminals deriving no string of terminals
+  duplicate       alternatives declared twice
+  cycle           rules deriving their own left side, like A -> A
+  reserved        unquoted terminals named like reserved words
+  all             all of the above"
         );
         return;
     }
-    let tables_type = if flags.clr {
-        generator::ParseTablesType::LR1
-    } else {
-        generator::ParseTablesType::LALR
-    };
+    if (flags.glr || flags.ll1) && flags.dense {
+        eprintln!("--glr and --ll1 can not be used with --dense");
+        process::exit(1);
+    }
     let mut grammar = String::new();
-    for line in io::stdin().lines() {
-        let line = line.unwrap();
-        grammar.push_str(&line);
-        grammar.push('\n');
-    }
-    let res = ParseTables::from_string(&grammar, tables_type);
-    match res {
-        Ok(tables) => {
-            println!("{}", tables.to_rust_source());
+    if let Some(file) = &flags.file {
+        grammar = fs::read_to_string(file).unwrap_or_else(|err| {
+            eprintln!("Can not read {}: {}", file, err);
+            process::exit(1);
+        });
+    } else {
+        for line in io::stdin().lines() {
+            let line = line.unwrap();
+            grammar.push_str(&line);
+            grammar.push('\n');
+        }
+    }
+    let mut sources = vec![Source::new(flags.file_name(), &grammar)];
+    match run(&mut sources, &flags) {
+        Ok(source) => {
+            println!("{}", source);
         }
         Err(err) => {
-            eprintln!("{}", err);
+            eprintln!("{}", err.render_sources(&sources));
             process::exit(1);
         }
     }
 }
+
+/// Imported grammar files are appended to `sources` to render errors in them.
+fn run(sources: &mut Vec<Source>, flags: &Flags) -> Result<String, GeneratorError> {
+    let tables_type = flags.tables_type;
+    let grammar = Grammar::from_sources(sources)?;
+    let mut denied = Vec::new();
+    for warning in grammar.lint() {
+        match flags.lints[&warning.lint] {
+            LintLevel::Allow => {}
+            LintLevel::Warn => eprintln!("warning: {}", warning.render_sources(sources)),
+            LintLevel::Deny => denied.push(warning),
+        }
+    }
+    if !denied.is_empty() {
+        return Err(GeneratorError::Lints(denied));
+    }
+    if flags.glr {
+        return run_glr(&grammar, flags);
+    }
+    if flags.ll1 {
+        return run_ll1(&grammar, flags);
+    }
+    let tables = if flags.all_conflicts {
+        ParseTables::from_grammar_all_conflicts(&grammar, tables_type)?
+    } else {
+        ParseTables::from_grammar(&grammar, tables_type)?
+    };
+    let mut extra = Vec::new();
+    if grammar.declares_tokens() {
+        extra.push(LexerTables::from_grammar(&grammar)?.to_rust_function());
+    }
+    if grammar.has_actions() && flags.dense {
+        extra.push(grammar.dense_actions_to_rust_source()?);
+    } else if grammar.has_actions() {
+        extra.push(grammar.actions_to_rust_source()?);
+    }
+    if flags.typed_ast {
+        extra.push(grammar.ast_to_rust_source());
+    }
+    if flags.dense {
+        return Ok(tables.to_dense_rust_source_with(&extra));
+    }
+    Ok(tables.to_rust_source_with(&extra))
+}
+
+/// Semantic actions need a single derivation, so they are not emitted for GLR tables.
+fn run_glr(grammar: &Grammar, flags: &Flags) -> Result<String, GeneratorError> {
+    let tables = GLRParseTables::from_grammar(grammar, flags.tables_type);
+    let mut extra = Vec::new();
+    if grammar.declares_tokens() {
+        extra.push(LexerTables::from_grammar(grammar)?.to_rust_function());
+    }
+    if flags.typed_ast {
+        extra.push(grammar.ast_to_rust_source());
+    }
+    Ok(tables.to_rust_source_with(&extra))
+}
+
+fn run_ll1(grammar: &Grammar, flags: &Flags) -> Result<String, GeneratorError> {
+    let tables = if flags.all_conflicts {
+        LL1ParseTables::from_grammar_all_conflicts(grammar)?
+    } else {
+        LL1ParseTables::from_grammar(grammar)?
+    };
+    let mut extra = Vec::new();
+    if grammar.declares_tokens() {
+        extra.push(LexerTables::from_grammar(grammar)?.to_rust_function());
+    }
+    if grammar.has_actions() {
+        extra.push(grammar.ll1_actions_to_rust_source()?);
+    }
+    if flags.typed_ast {
+        extra.push(grammar.ast_to_rust_source());
+    }
+    Ok(tables.to_rust_source_with(&extra))
+}
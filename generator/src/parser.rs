@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, convert::Infallible, fmt::Display};
+
+/// Terminal matching a part of the input skipped by the error recovery.
+pub const ERROR_TERMINAL_NAME: &str = "error";
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug, PartialOrd, Ord)]
 pub enum Term {
@@ -12,7 +15,7 @@ pub struct Rule {
     pub right: Vec<Term>,
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 pub enum LR1Action {
     Reduce(Rule),
     Shift(i32),
@@ -22,6 +25,8 @@ pub enum LR1Action {
 #[derive(PartialEq, Eq, Debug)]
 pub struct ParseTables {
     pub start: i32,
+    /// start states of the nonterminals declared in `axiom`, including the axiom
+    pub entries: HashMap<Nonterminal, i32>,
     pub action: HashMap<(i32, TerminalOrFinish), LR1Action>,
     pub goto: HashMap<(i32, Nonterminal), i32>,
 }
@@ -32,17 +37,30 @@ pub enum TerminalOrFinish {
     Finish,
 }
 
-impl ToString for TerminalOrFinish {
-    fn to_string(&self) -> String {
+impl Display for TerminalOrFinish {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            TerminalOrFinish::Terminal(t) => t.0.clone(),
-            TerminalOrFinish::Finish => String::from("$"),
+            TerminalOrFinish::Terminal(t) => f.write_str(&t.0),
+            TerminalOrFinish::Finish => f.write_str("$"),
         }
     }
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug, PartialOrd, Ord)]
 pub struct Nonterminal(pub String);
+
+impl Nonterminal {
+    /// Helper nonterminals introduced by the generator for groups, `?`, `*` and `+`
+    /// contain `<` in their names, so they can not clash with the ones from a grammar.
+    pub fn is_generated(&self) -> bool {
+        self.0.contains('<')
+    }
+
+    fn list_name(&self) -> &str {
+        self.0.trim_end_matches(['*', '+'])
+    }
+}
+
 #[derive(PartialEq, Eq, Hash, Clone, Debug, PartialOrd, Ord)]
 
 pub struct Terminal(pub String);
@@ -53,10 +71,12 @@ pub struct Token<T> {
     pub attribute: T,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum ParseTree<T> {
     Internal(Nonterminal, Vec<ParseTree<T>>),
     Leaf(Token<T>),
+    /// The `error` terminal with the tokens skipped by the error recovery.
+    Error(Vec<Token<T>>),
 }
 
 impl<T> ParseTree<T> {
@@ -73,11 +93,69 @@ impl<T> ParseTree<T> {
         }
         None
     }
+
+    /// Creates an internal node. Generated `X*` and `X+` lists are right-recursive
+    /// in the grammar, so their tails are spliced to keep all elements in one node.
+    pub fn from_children(nterm: Nonterminal, children: Vec<ParseTree<T>>) -> ParseTree<T> {
+        if !nterm.is_generated() {
+            return ParseTree::Internal(nterm, children);
+        }
+        let mut res = Vec::new();
+        for child in children {
+            match child {
+                ParseTree::Internal(other, grandchildren)
+                    if other.0.ends_with('*') && other.list_name() == nterm.list_name() =>
+                {
+                    res.extend(grandchildren);
+                }
+                child => res.push(child),
+            }
+        }
+        ParseTree::Internal(nterm, res)
+    }
 }
 
+/// Unexpected `token`, `expected` are the terminals with an action in the state
+/// of the parser.
 #[derive(Debug)]
 pub struct ParseError<T> {
     pub token: Token<T>,
+    pub expected: Vec<TerminalOrFinish>,
+}
+
+impl<T> ParseError<T> {
+    /// Formats the expected terminals as "expected one of `+`, `)`, EOF".
+    pub fn expected_to_string(&self) -> String {
+        let names: Vec<String> = self
+            .expected
+            .iter()
+            .map(|x| match x {
+                TerminalOrFinish::Terminal(t) => format!("`{}`", t.0),
+                TerminalOrFinish::Finish => String::from("EOF"),
+            })
+            .collect();
+        match names.len() {
+            0 => String::from("nothing expected"),
+            1 => format!("expected {}", names[0]),
+            _ => format!("expected one of {}", names.join(", ")),
+        }
+    }
+}
+
+/// Error of a parser reading tokens from an iterator: the error of the input,
+/// e.g. `LexError`, a syntax error, or an iterator without tokens, which gives
+/// no attribute for the end of the input.
+#[derive(Debug)]
+pub enum StreamError<T, E> {
+    Input(E),
+    Parse(ParseError<T>),
+    Empty,
+}
+
+impl<T, E> From<ParseError<T>> for StreamError<T, E> {
+    fn from(err: ParseError<T>) -> Self {
+        StreamError::Parse(err)
+    }
 }
 
 fn err_on_none<T, P: Clone>(res: Option<T>, token: &Token<P>) -> Result<T, ParseError<P>> {
@@ -85,46 +163,688 @@ fn err_on_none<T, P: Clone>(res: Option<T>, token: &Token<P>) -> Result<T, Parse
         Some(v) => Ok(v),
         None => Err(ParseError {
             token: token.clone(),
+            expected: Vec::new(),
         }),
     }
 }
 
-impl<T: Clone> ParseTree<T> {
-    pub fn from_tables_and_tokens(
-        tables: &ParseTables,
+impl ParseTables {
+    /// Returns the sorted terminals with an action in the state, without `error`.
+    pub fn get_expected(&self, state: i32) -> Vec<TerminalOrFinish> {
+        let mut res: Vec<TerminalOrFinish> = self
+            .action
+            .keys()
+            .filter(|(x, t)| {
+                *x == state
+                    && !matches!(t, TerminalOrFinish::Terminal(t) if t.0 == ERROR_TERMINAL_NAME)
+            })
+            .map(|(_, t)| t.clone())
+            .collect();
+        res.sort();
+        res
+    }
+
+    /// Returns the start state of the nonterminal declared in `axiom`.
+    /// Panics if the grammar has no such entry.
+    pub fn get_entry(&self, entry: &str) -> i32 {
+        match self.entries.get(&Nonterminal(String::from(entry))) {
+            Some(state) => *state,
+            None => panic!("{} is not an entry of the grammar", entry),
+        }
+    }
+
+    fn unexpected<T: Clone>(&self, state: i32, token: &Token<T>) -> ParseError<T> {
+        ParseError {
+            token: token.clone(),
+            expected: self.get_expected(state),
+        }
+    }
+
+    /// Runs the parser, building values of tokens with `shift` and values of
+    /// reduced rules from the values of their right parts with `reduce`.
+    pub fn parse_with<T, V, S, R>(
+        &self,
         tokens: &[Token<T>],
-    ) -> Result<ParseTree<T>, ParseError<T>> {
-        let mut states = vec![tables.start];
-        let mut trees: Vec<ParseTree<T>> = Vec::new();
-        let mut token_index = 0;
+        shift: S,
+        reduce: R,
+    ) -> Result<V, ParseError<T>>
+    where
+        T: Clone,
+        S: FnMut(&Token<T>) -> V,
+        R: FnMut(&Rule, Vec<V>) -> V,
+    {
+        self.parse_slice_from(self.start, tokens, shift, reduce)
+    }
+
+    /// Same as `parse_with`, but parses the tokens as the entry declared in `axiom`
+    /// instead of the axiom.
+    pub fn parse_entry_with<T, V, S, R>(
+        &self,
+        entry: &str,
+        tokens: &[Token<T>],
+        shift: S,
+        reduce: R,
+    ) -> Result<V, ParseError<T>>
+    where
+        T: Clone,
+        S: FnMut(&Token<T>) -> V,
+        R: FnMut(&Rule, Vec<V>) -> V,
+    {
+        self.parse_slice_from(self.get_entry(entry), tokens, shift, reduce)
+    }
+
+    /// Same as `parse_with`, but the tokens are pulled from the iterator one at a time.
+    /// If the iterator ends without the `Finish` token, its end is read as `Finish`.
+    pub fn parse_iter_with<T, V, I, S, R>(
+        &self,
+        tokens: I,
+        shift: S,
+        reduce: R,
+    ) -> Result<V, StreamError<T, Infallible>>
+    where
+        T: Clone,
+        I: IntoIterator<Item = Token<T>>,
+        S: FnMut(&Token<T>) -> V,
+        R: FnMut(&Rule, Vec<V>) -> V,
+    {
+        let tokens = tokens.into_iter().map(Ok::<_, Infallible>);
+        self.try_parse_iter_from(self.start, tokens, shift, reduce)
+    }
+
+    /// Panics on an empty slice, tokens of the lexer always end with `Finish`.
+    fn parse_slice_from<T, V, S, R>(
+        &self,
+        start: i32,
+        tokens: &[Token<T>],
+        shift: S,
+        reduce: R,
+    ) -> Result<V, ParseError<T>>
+    where
+        T: Clone,
+        S: FnMut(&Token<T>) -> V,
+        R: FnMut(&Rule, Vec<V>) -> V,
+    {
+        let tokens = tokens.iter().cloned().map(Ok::<_, Infallible>);
+        self.try_parse_iter_from(start, tokens, shift, reduce)
+            .map_err(|err| match err {
+                StreamError::Parse(err) => err,
+                StreamError::Input(err) => match err {},
+                StreamError::Empty => panic!("no tokens to parse"),
+            })
+    }
+
+    /// Same as `parse_iter_with` for iterators of results, e.g. a lexer: the first error
+    /// of the input stops the parser.
+    pub fn try_parse_iter_with<T, E, V, I, S, R>(
+        &self,
+        tokens: I,
+        shift: S,
+        reduce: R,
+    ) -> Result<V, StreamError<T, E>>
+    where
+        T: Clone,
+        I: IntoIterator<Item = Result<Token<T>, E>>,
+        S: FnMut(&Token<T>) -> V,
+        R: FnMut(&Rule, Vec<V>) -> V,
+    {
+        self.try_parse_iter_from(self.start, tokens, shift, reduce)
+    }
+
+    fn try_parse_iter_from<T, E, V, I, S, R>(
+        &self,
+        start: i32,
+        tokens: I,
+        mut shift: S,
+        mut reduce: R,
+    ) -> Result<V, StreamError<T, E>>
+    where
+        T: Clone,
+        I: IntoIterator<Item = Result<Token<T>, E>>,
+        S: FnMut(&Token<T>) -> V,
+        R: FnMut(&Rule, Vec<V>) -> V,
+    {
+        let mut tokens = tokens.into_iter();
+        let Some(first) = tokens.next() else {
+            return Err(StreamError::Empty);
+        };
+        let first = first.map_err(StreamError::Input)?;
+        let mut last = first.attribute.clone();
+        let mut next_token = || -> Result<Token<T>, StreamError<T, E>> {
+            match tokens.next() {
+                Some(token) => {
+                    let token = token.map_err(StreamError::Input)?;
+                    last = token.attribute.clone();
+                    Ok(token)
+                }
+                // the end of the input is read as `Finish` with the attribute of the last token
+                None => Ok(Token {
+                    tag: TerminalOrFinish::Finish,
+                    attribute: last.clone(),
+                }),
+            }
+        };
+        let mut states = vec![start];
+        let mut values: Vec<V> = Vec::new();
+        let mut token = first;
         loop {
-            let token = &tokens[token_index];
-            let cur_state = err_on_none(states.last(), token)?;
-            let action = err_on_none(tables.action.get(&(*cur_state, token.tag.clone())), token)?;
+            let cur_state = err_on_none(states.last(), &token)?;
+            let Some(action) = self.action.get(&(*cur_state, token.tag.clone())) else {
+                return Err(self.unexpected(*cur_state, &token).into());
+            };
             match action {
                 LR1Action::Shift(state) => {
                     states.push(*state);
-                    trees.push(Self::Leaf(token.clone()));
-                    token_index += 1;
+                    values.push(shift(&token));
+                    token = next_token()?;
                 }
                 LR1Action::Reduce(rule) => {
-                    let mut children: Vec<ParseTree<T>> = Vec::new();
+                    let mut children: Vec<V> = Vec::new();
                     for _ in 0..rule.right.len() {
                         states.pop();
-                        children.push(err_on_none(trees.pop(), token)?);
+                        children.push(err_on_none(values.pop(), &token)?);
                     }
                     children.reverse();
-                    trees.push(ParseTree::Internal(rule.left.clone(), children));
-                    let cur = err_on_none(states.last(), token)?;
-                    let next = err_on_none(tables.goto.get(&(*cur, rule.left.clone())), token)?;
+                    values.push(reduce(rule, children));
+                    let cur = err_on_none(states.last(), &token)?;
+                    let next = err_on_none(self.goto.get(&(*cur, rule.left.clone())), &token)?;
+                    states.push(*next);
+                }
+                LR1Action::Accept => {
+                    return Ok(err_on_none(values.pop(), &token)?);
+                }
+            }
+        }
+    }
+
+    /// Runs the parser with panic mode error recovery. On an unexpected token the error
+    /// is recorded, states are popped until one that shifts `error`, the value of `error`
+    /// is built with `error` from the tokens skipped until one acceptable after it.
+    /// Returns `None` instead of the value if no state shifts `error` or the input ends.
+    pub fn parse_with_recovery<T, V, S, R, E>(
+        &self,
+        tokens: &[Token<T>],
+        mut shift: S,
+        mut reduce: R,
+        mut error: E,
+    ) -> (Option<V>, Vec<ParseError<T>>)
+    where
+        T: Clone,
+        S: FnMut(&Token<T>) -> V,
+        R: FnMut(&Rule, Vec<V>) -> V,
+        E: FnMut(Vec<Token<T>>) -> V,
+    {
+        let error_tag = TerminalOrFinish::Terminal(Terminal(String::from(ERROR_TERMINAL_NAME)));
+        let mut errors = Vec::new();
+        let mut states = vec![self.start];
+        let mut values: Vec<V> = Vec::new();
+        let mut token_index = 0;
+        let mut last_recovery = None;
+        loop {
+            let token = &tokens[token_index];
+            let Some(cur_state) = states.last() else {
+                return (None, errors);
+            };
+            match self.action.get(&(*cur_state, token.tag.clone())) {
+                Some(LR1Action::Shift(state)) => {
+                    states.push(*state);
+                    values.push(shift(token));
+                    token_index += 1;
+                }
+                Some(LR1Action::Reduce(rule)) => {
+                    let children = values.split_off(values.len() - rule.right.len());
+                    states.truncate(states.len() - rule.right.len());
+                    values.push(reduce(rule, children));
+                    let Some(next) = states
+                        .last()
+                        .and_then(|x| self.goto.get(&(*x, rule.left.clone())))
+                    else {
+                        return (None, errors);
+                    };
                     states.push(*next);
                 }
+                Some(LR1Action::Accept) => {
+                    return (values.pop(), errors);
+                }
+                None => {
+                    let mut skipped = Vec::new();
+                    // an error right after a recovery is not reported again, but the token
+                    // is skipped to make progress
+                    if last_recovery == Some(token_index) {
+                        if token.tag == TerminalOrFinish::Finish {
+                            return (None, errors);
+                        }
+                        skipped.push(token.clone());
+                        token_index += 1;
+                    } else {
+                        errors.push(self.unexpected(*cur_state, token));
+                    }
+                    let next = loop {
+                        let Some(state) = states.last() else {
+                            return (None, errors);
+                        };
+                        if let Some(LR1Action::Shift(next)) =
+                            self.action.get(&(*state, error_tag.clone()))
+                        {
+                            break *next;
+                        }
+                        states.pop();
+                        values.pop();
+                    };
+                    while !self
+                        .action
+                        .contains_key(&(next, tokens[token_index].tag.clone()))
+                    {
+                        if tokens[token_index].tag == TerminalOrFinish::Finish {
+                            return (None, errors);
+                        }
+                        skipped.push(tokens[token_index].clone());
+                        token_index += 1;
+                    }
+                    states.push(next);
+                    values.push(error(skipped));
+                    last_recovery = Some(token_index);
+                }
+            }
+        }
+    }
+}
+
+/// Result of `PushParser::push`.
+#[derive(Debug)]
+pub enum PushResult<T> {
+    /// The token is shifted, the parser waits for the next one.
+    NeedMore,
+    Accepted(ParseTree<T>),
+    /// The token is rejected, the parser is left as it was before the token.
+    Error(ParseError<T>),
+}
+
+/// Resumable parser fed one token at a time. Cloning it saves a checkpoint to continue
+/// from, e.g. before an edit or a line of a REPL.
+#[derive(Clone, Debug)]
+pub struct PushParser<'a, T> {
+    tables: &'a ParseTables,
+    states: Vec<i32>,
+    trees: Vec<ParseTree<T>>,
+}
+
+impl<'a, T: Clone> PushParser<'a, T> {
+    pub fn new(tables: &'a ParseTables) -> Self {
+        PushParser {
+            tables,
+            states: vec![tables.start],
+            trees: Vec::new(),
+        }
+    }
+
+    /// Parser of the entry declared in `axiom` instead of the axiom.
+    pub fn for_entry(tables: &'a ParseTables, entry: &str) -> Self {
+        PushParser {
+            tables,
+            states: vec![tables.get_entry(entry)],
+            trees: Vec::new(),
+        }
+    }
+
+    /// Returns the sorted terminals acceptable as the next token, without `error`.
+    pub fn get_expected(&self) -> Vec<TerminalOrFinish> {
+        let state = self.states.last().copied().unwrap_or(self.tables.start);
+        self.tables.get_expected(state)
+    }
+
+    /// Reduces the stacks until the token is shifted or accepted. The last token has
+    /// to be `Finish`.
+    pub fn push(&mut self, token: Token<T>) -> PushResult<T> {
+        if !self.accepts(&token.tag) {
+            let state = self.states.last().copied().unwrap_or(self.tables.start);
+            return PushResult::Error(self.tables.unexpected(state, &token));
+        }
+        loop {
+            let state = self.states[self.states.len() - 1];
+            match &self.tables.action[&(state, token.tag.clone())] {
+                LR1Action::Shift(next) => {
+                    self.states.push(*next);
+                    self.trees.push(ParseTree::Leaf(token));
+                    return PushResult::NeedMore;
+                }
+                LR1Action::Reduce(rule) => {
+                    let length = rule.right.len();
+                    let children = self.trees.split_off(self.trees.len() - length);
+                    self.states.truncate(self.states.len() - length);
+                    let tree = ParseTree::from_children(rule.left.clone(), children);
+                    self.trees.push(tree);
+                    let cur = self.states[self.states.len() - 1];
+                    self.states
+                        .push(self.tables.goto[&(cur, rule.left.clone())]);
+                }
                 LR1Action::Accept => {
-                    return err_on_none(trees.pop(), token);
+                    let tree = self.trees.pop().expect("no tree to accept");
+                    return PushResult::Accepted(tree);
+                }
+            }
+        }
+    }
+
+    /// Runs the reductions on a copy of the top of the state stack, so a rejected
+    /// token does not change the parser.
+    fn accepts(&self, tag: &TerminalOrFinish) -> bool {
+        let mut depth = self.states.len();
+        let mut pushed: Vec<i32> = Vec::new();
+        loop {
+            let state = match pushed.last() {
+                Some(state) => *state,
+                None if depth > 0 => self.states[depth - 1],
+                None => return false,
+            };
+            match self.tables.action.get(&(state, tag.clone())) {
+                None => return false,
+                Some(LR1Action::Shift(_) | LR1Action::Accept) => return true,
+                Some(LR1Action::Reduce(rule)) => {
+                    let length = rule.right.len();
+                    let from_pushed = length.min(pushed.len());
+                    pushed.truncate(pushed.len() - from_pushed);
+                    if length - from_pushed > depth {
+                        return false;
+                    }
+                    depth -= length - from_pushed;
+                    let cur = match pushed.last() {
+                        Some(state) => *state,
+                        None if depth > 0 => self.states[depth - 1],
+                        None => return false,
+                    };
+                    match self.tables.goto.get(&(cur, rule.left.clone())) {
+                        Some(next) => pushed.push(*next),
+                        None => return false,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Symbol of a rule of `DenseParseTables`, terminal 0 is `Finish`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
+pub enum SymbolId {
+    Terminal(usize),
+    Nonterminal(usize),
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct DenseRule {
+    pub left: usize,
+    pub right: &'static [SymbolId],
+}
+
+/// Sparse table compressed by row displacement: the entry of the row `r` and the column `c`
+/// is `value[base[r] + c]` if `check[base[r] + c] == r`.
+#[derive(PartialEq, Eq, Debug)]
+pub struct DisplacedTable {
+    pub base: &'static [usize],
+    pub check: &'static [i32],
+    pub value: &'static [i32],
+}
+
+impl DisplacedTable {
+    pub fn get(&self, row: usize, column: usize) -> Option<i32> {
+        let index = self.base.get(row)? + column;
+        match self.check.get(index) {
+            Some(x) if *x == row as i32 => Some(self.value[index]),
+            _ => None,
+        }
+    }
+}
+
+/// `ParseTables` in static arrays. Terminal `i` is `terminals[i - 1]`, terminal 0 is
+/// `Finish`, nonterminal `i` is `nonterminals[i]`. Actions are encoded as `3 * s` for
+/// shift to the state `s`, `3 * r + 1` for reduce by `rules[r]` and `2` for accept.
+#[derive(PartialEq, Eq, Debug)]
+pub struct DenseParseTables {
+    pub start: usize,
+    /// start states of the nonterminals declared in `axiom`, sorted by name
+    pub entries: &'static [(&'static str, usize)],
+    /// sorted for the lookup by a binary search
+    pub terminals: &'static [&'static str],
+    pub nonterminals: &'static [&'static str],
+    pub rules: &'static [DenseRule],
+    pub action: DisplacedTable,
+    pub goto: DisplacedTable,
+}
+
+impl DenseParseTables {
+    pub fn get_terminal_id(&self, tag: &TerminalOrFinish) -> Option<usize> {
+        match tag {
+            TerminalOrFinish::Finish => Some(0),
+            TerminalOrFinish::Terminal(t) => self
+                .terminals
+                .binary_search(&t.0.as_str())
+                .ok()
+                .map(|x| x + 1),
+        }
+    }
+
+    pub fn get_rule(&self, index: usize) -> Rule {
+        let rule = &self.rules[index];
+        Rule {
+            left: Nonterminal(String::from(self.nonterminals[rule.left])),
+            right: rule
+                .right
+                .iter()
+                .map(|x| match x {
+                    SymbolId::Terminal(t) => {
+                        Term::Terminal(Terminal(String::from(self.terminals[t - 1])))
+                    }
+                    SymbolId::Nonterminal(n) => {
+                        Term::Nonterminal(Nonterminal(String::from(self.nonterminals[*n])))
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the sorted terminals with an action in the state, without `error`.
+    pub fn get_expected(&self, state: usize) -> Vec<TerminalOrFinish> {
+        let mut res: Vec<TerminalOrFinish> = (1..=self.terminals.len())
+            .filter(|x| {
+                self.terminals[x - 1] != ERROR_TERMINAL_NAME && self.action.get(state, *x).is_some()
+            })
+            .map(|x| TerminalOrFinish::Terminal(Terminal(String::from(self.terminals[x - 1]))))
+            .collect();
+        if self.action.get(state, 0).is_some() {
+            res.push(TerminalOrFinish::Finish);
+        }
+        res
+    }
+
+    /// Returns the start state of the nonterminal declared in `axiom`.
+    /// Panics if the grammar has no such entry.
+    pub fn get_entry(&self, entry: &str) -> usize {
+        match self.entries.binary_search_by_key(&entry, |(x, _)| x) {
+            Ok(i) => self.entries[i].1,
+            Err(_) => panic!("{} is not an entry of the grammar", entry),
+        }
+    }
+
+    /// Same as `ParseTables::parse_with`, but `reduce` gets the index of the rule in `rules`.
+    pub fn parse_with<T, V, S, R>(
+        &self,
+        tokens: &[Token<T>],
+        shift: S,
+        reduce: R,
+    ) -> Result<V, ParseError<T>>
+    where
+        T: Clone,
+        S: FnMut(&Token<T>) -> V,
+        R: FnMut(usize, Vec<V>) -> V,
+    {
+        self.parse_from(self.start, tokens, shift, reduce)
+    }
+
+    /// Same as `parse_with`, but parses the tokens as the entry declared in `axiom`.
+    pub fn parse_entry_with<T, V, S, R>(
+        &self,
+        entry: &str,
+        tokens: &[Token<T>],
+        shift: S,
+        reduce: R,
+    ) -> Result<V, ParseError<T>>
+    where
+        T: Clone,
+        S: FnMut(&Token<T>) -> V,
+        R: FnMut(usize, Vec<V>) -> V,
+    {
+        self.parse_from(self.get_entry(entry), tokens, shift, reduce)
+    }
+
+    fn parse_from<T, V, S, R>(
+        &self,
+        start: usize,
+        tokens: &[Token<T>],
+        mut shift: S,
+        mut reduce: R,
+    ) -> Result<V, ParseError<T>>
+    where
+        T: Clone,
+        S: FnMut(&Token<T>) -> V,
+        R: FnMut(usize, Vec<V>) -> V,
+    {
+        let mut states = vec![start];
+        let mut values: Vec<V> = Vec::new();
+        let mut token_index = 0;
+        loop {
+            let token = &tokens[token_index];
+            let cur_state = *err_on_none(states.last(), token)?;
+            let action = self
+                .get_terminal_id(&token.tag)
+                .and_then(|x| self.action.get(cur_state, x));
+            let Some(action) = action else {
+                return Err(ParseError {
+                    token: token.clone(),
+                    expected: self.get_expected(cur_state),
+                });
+            };
+            let index = (action / 3) as usize;
+            match action % 3 {
+                0 => {
+                    states.push(index);
+                    values.push(shift(token));
+                    token_index += 1;
+                }
+                1 => {
+                    let rule = &self.rules[index];
+                    let children = values.split_off(values.len() - rule.right.len());
+                    states.truncate(states.len() - rule.right.len());
+                    values.push(reduce(index, children));
+                    let cur = *err_on_none(states.last(), token)?;
+                    let next = err_on_none(self.goto.get(cur, rule.left), token)?;
+                    states.push(next as usize);
+                }
+                _ => {
+                    return err_on_none(values.pop(), token);
                 }
             }
         }
     }
+}
+
+impl<T: Clone> ParseTree<T> {
+    pub fn from_tables_and_tokens(
+        tables: &ParseTables,
+        tokens: &[Token<T>],
+    ) -> Result<ParseTree<T>, ParseError<T>> {
+        tables.parse_with(
+            tokens,
+            |token| ParseTree::Leaf(token.clone()),
+            |rule, children| ParseTree::from_children(rule.left.clone(), children),
+        )
+    }
+
+    /// Same as `from_tables_and_tokens`, but parses the tokens as the entry declared in
+    /// `axiom`, e.g. a single statement of a program.
+    pub fn from_tables_and_tokens_for(
+        tables: &ParseTables,
+        entry: &str,
+        tokens: &[Token<T>],
+    ) -> Result<ParseTree<T>, ParseError<T>> {
+        tables.parse_entry_with(
+            entry,
+            tokens,
+            |token| ParseTree::Leaf(token.clone()),
+            |rule, children| ParseTree::from_children(rule.left.clone(), children),
+        )
+    }
+
+    /// Same as `from_tables_and_tokens`, but the tokens are pulled from the iterator,
+    /// e.g. `LexerTables::tokens`, so the input is not lexed in advance.
+    pub fn from_tables_and_token_iter<E, I>(
+        tables: &ParseTables,
+        tokens: I,
+    ) -> Result<ParseTree<T>, StreamError<T, E>>
+    where
+        I: IntoIterator<Item = Result<Token<T>, E>>,
+    {
+        tables.try_parse_iter_with(
+            tokens,
+            |token| ParseTree::Leaf(token.clone()),
+            |rule, children| ParseTree::from_children(rule.left.clone(), children),
+        )
+    }
+
+    /// Same as `from_tables_and_tokens`, but recovers from syntax errors with the `error`
+    /// terminal of the grammar. Returns all found errors and the tree with `Error` nodes,
+    /// the tree is `None` if the parser could not recover.
+    pub fn from_tables_and_tokens_with_recovery(
+        tables: &ParseTables,
+        tokens: &[Token<T>],
+    ) -> (Option<ParseTree<T>>, Vec<ParseError<T>>) {
+        tables.parse_with_recovery(
+            tokens,
+            |token| ParseTree::Leaf(token.clone()),
+            |rule, children| ParseTree::from_children(rule.left.clone(), children),
+            ParseTree::Error,
+        )
+    }
+
+    pub fn from_ll1_tables_and_tokens(
+        tables: &LL1ParseTables,
+        tokens: &[Token<T>],
+    ) -> Result<ParseTree<T>, ParseError<T>> {
+        tables.parse_with(
+            tokens,
+            |token| ParseTree::Leaf(token.clone()),
+            |rule, children| ParseTree::from_children(rule.left.clone(), children),
+        )
+    }
+
+    pub fn from_dense_tables_and_tokens(
+        tables: &DenseParseTables,
+        tokens: &[Token<T>],
+    ) -> Result<ParseTree<T>, ParseError<T>> {
+        tables.parse_with(
+            tokens,
+            |token| ParseTree::Leaf(token.clone()),
+            |rule, children| {
+                let left = tables.nonterminals[tables.rules[rule].left];
+                ParseTree::from_children(Nonterminal(String::from(left)), children)
+            },
+        )
+    }
+
+    /// Same as `from_tables_and_tokens_for` for dense tables.
+    pub fn from_dense_tables_and_tokens_for(
+        tables: &DenseParseTables,
+        entry: &str,
+        tokens: &[Token<T>],
+    ) -> Result<ParseTree<T>, ParseError<T>> {
+        tables.parse_entry_with(
+            entry,
+            tokens,
+            |token| ParseTree::Leaf(token.clone()),
+            |rule, children| {
+                let left = tables.nonterminals[tables.rules[rule].left];
+                ParseTree::from_children(Nonterminal(String::from(left)), children)
+            },
+        )
+    }
 
     pub fn to_graphviz(&self) -> String {
         let mut counter = 0;
@@ -150,58 +870,1901 @@ impl<T: Clone> ParseTree<T> {
                 }
             }
             ParseTree::Leaf(token) => {
-                result += format!("{} [label=\"{}\"]\n", id, token.tag.to_string()).as_ref();
+                result += format!("{} [label=\"{}\"]\n", id, token.tag).as_ref();
+            }
+            ParseTree::Error(_) => {
+                result += format!("{} [label=\"{}\"]\n", id, ERROR_TERMINAL_NAME).as_ref();
             }
         }
         result
     }
 }
 
-//@START_PARSE_TABLES@
+/// LL(1) parse table: the rule to expand the nonterminal with on the lookahead.
+#[derive(PartialEq, Eq, Debug)]
+pub struct LL1ParseTables {
+    pub axiom: Nonterminal,
+    pub table: HashMap<(Nonterminal, TerminalOrFinish), Rule>,
+}
 
-pub fn get_parse_tables() -> ParseTables {
-    let action = [
-        (
-            (
-                3,
-                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
-            ),
-            LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("I")),
-                right: vec![],
+impl LL1ParseTables {
+    /// Returns the sorted lookaheads the nonterminal can start with, without `error`.
+    pub fn get_expected(&self, nterm: &Nonterminal) -> Vec<TerminalOrFinish> {
+        let mut res: Vec<TerminalOrFinish> = self
+            .table
+            .keys()
+            .filter(|(x, t)| {
+                x == nterm
+                    && !matches!(t, TerminalOrFinish::Terminal(t) if t.0 == ERROR_TERMINAL_NAME)
+            })
+            .map(|(_, t)| t.clone())
+            .collect();
+        res.sort();
+        res
+    }
+
+    /// Same as `ParseTables::parse_with`, rules are expanded top-down, so `reduce`
+    /// is called when the last symbol of the rule is parsed.
+    pub fn parse_with<T, V, S, R>(
+        &self,
+        tokens: &[Token<T>],
+        mut shift: S,
+        mut reduce: R,
+    ) -> Result<V, ParseError<T>>
+    where
+        T: Clone,
+        S: FnMut(&Token<T>) -> V,
+        R: FnMut(&Rule, Vec<V>) -> V,
+    {
+        let mut token_index = 0;
+        // expanded rules with the values of their parsed symbols
+        let mut rules: Vec<(&Rule, Vec<V>)> = Vec::new();
+        let mut next = self.axiom.clone();
+        loop {
+            let token = &tokens[token_index];
+            let Some(rule) = self.table.get(&(next.clone(), token.tag.clone())) else {
+                return Err(ParseError {
+                    token: token.clone(),
+                    expected: self.get_expected(&next),
+                });
+            };
+            rules.push((rule, Vec::new()));
+            loop {
+                let token = &tokens[token_index];
+                let (rule, children) = err_on_none(rules.last_mut(), token)?;
+                match rule.right.get(children.len()) {
+                    Some(Term::Terminal(t))
+                        if token.tag == TerminalOrFinish::Terminal(t.clone()) =>
+                    {
+                        children.push(shift(token));
+                        token_index += 1;
+                    }
+                    Some(Term::Terminal(t)) => {
+                        return Err(ParseError {
+                            token: token.clone(),
+                            expected: vec![TerminalOrFinish::Terminal(t.clone())],
+                        });
+                    }
+                    Some(Term::Nonterminal(nterm)) => {
+                        next = nterm.clone();
+                        break;
+                    }
+                    None => {
+                        let (rule, children) = err_on_none(rules.pop(), token)?;
+                        let value = reduce(rule, children);
+                        match rules.last_mut() {
+                            Some((_, children)) => children.push(value),
+                            None if token.tag == TerminalOrFinish::Finish => return Ok(value),
+                            None => {
+                                return Err(ParseError {
+                                    token: token.clone(),
+                                    expected: vec![TerminalOrFinish::Finish],
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parse tables keeping all actions of unresolved conflicts, the input is parsed with
+/// `ParseForest::from_tables_and_tokens`.
+#[derive(PartialEq, Eq, Debug)]
+pub struct GLRParseTables {
+    pub start: i32,
+    pub action: HashMap<(i32, TerminalOrFinish), Vec<LR1Action>>,
+    pub goto: HashMap<(i32, Nonterminal), i32>,
+}
+
+impl GLRParseTables {
+    /// Returns the sorted terminals with an action in any of the states, without `error`.
+    pub fn get_expected(&self, states: &[i32]) -> Vec<TerminalOrFinish> {
+        let mut res: Vec<TerminalOrFinish> = self
+            .action
+            .keys()
+            .filter(|(x, t)| {
+                states.contains(x)
+                    && !matches!(t, TerminalOrFinish::Terminal(t) if t.0 == ERROR_TERMINAL_NAME)
+            })
+            .map(|(_, t)| t.clone())
+            .collect();
+        res.sort();
+        res.dedup();
+        res
+    }
+
+    fn get_reductions(&self, state: i32, token: &TerminalOrFinish) -> Vec<&Rule> {
+        let actions = self.action.get(&(state, token.clone()));
+        let reductions = actions.into_iter().flatten().filter_map(|x| match x {
+            LR1Action::Reduce(rule) => Some(rule),
+            _ => None,
+        });
+        reductions.collect()
+    }
+}
+
+/// Shared packed parse forest: subtrees over the same part of the input are shared,
+/// an internal node keeps the children of every derivation.
+#[derive(Debug)]
+pub struct ParseForest<T> {
+    pub nodes: Vec<ForestNode<T>>,
+    pub root: usize,
+}
+
+#[derive(Debug)]
+pub enum ForestNode<T> {
+    Leaf(Token<T>),
+    /// Indices of the children in `ParseForest::nodes`, one list per derivation.
+    Internal(Nonterminal, Vec<Vec<usize>>),
+}
+
+/// Vertex of the graph-structured stack, edges lead to the previous vertices and are
+/// labeled with forest nodes.
+struct StackNode {
+    state: i32,
+    level: usize,
+    edges: Vec<(usize, usize)>,
+}
+
+impl<T: Clone> ParseForest<T> {
+    /// Generalised LR parser: on a conflict the stack is split, stacks in equal states
+    /// are merged, so every derivation of the input ends up in the forest.
+    pub fn from_tables_and_tokens(
+        tables: &GLRParseTables,
+        tokens: &[Token<T>],
+    ) -> Result<ParseForest<T>, ParseError<T>> {
+        let mut forest = ParseForest {
+            nodes: Vec::new(),
+            root: 0,
+        };
+        // nodes of nonterminals by the levels of their start and end
+        let mut packed: HashMap<(Nonterminal, usize, usize), usize> = HashMap::new();
+        let mut stack = vec![StackNode {
+            state: tables.start,
+            level: 0,
+            edges: Vec::new(),
+        }];
+        let mut frontier = vec![0];
+        let mut level = 0;
+        loop {
+            let token = &tokens[level];
+            let mut by_state: HashMap<i32, usize> =
+                frontier.iter().map(|x| (stack[*x].state, *x)).collect();
+            // reductions are limited to the paths starting with the given edge
+            let mut queue: Vec<(usize, &Rule, Option<usize>)> = Vec::new();
+            for node in &frontier {
+                for rule in tables.get_reductions(stack[*node].state, &token.tag) {
+                    queue.push((*node, rule, None));
+                }
+            }
+            while let Some((node, rule, edge)) = queue.pop() {
+                for (prev, children) in get_paths(&stack, node, rule.right.len(), edge) {
+                    let start = stack[prev].level;
+                    let Some(next) = tables.goto.get(&(stack[prev].state, rule.left.clone()))
+                    else {
+                        continue;
+                    };
+                    let key = (rule.left.clone(), start, level);
+                    let label = *packed.entry(key).or_insert_with(|| {
+                        forest
+                            .nodes
+                            .push(ForestNode::Internal(rule.left.clone(), Vec::new()));
+                        forest.nodes.len() - 1
+                    });
+                    if let ForestNode::Internal(_, alternatives) = &mut forest.nodes[label] {
+                        if !alternatives.contains(&children) {
+                            alternatives.push(children);
+                        }
+                    }
+                    if let Some(other) = by_state.get(next) {
+                        let other = *other;
+                        if stack[other].edges.iter().any(|(x, _)| *x == prev) {
+                            continue;
+                        }
+                        stack[other].edges.push((prev, label));
+                        let edge = stack[other].edges.len() - 1;
+                        // paths through the new edge from the vertices above it
+                        for node in &frontier {
+                            let is_above = *node != other
+                                && stack[*node]
+                                    .edges
+                                    .iter()
+                                    .any(|(x, _)| stack[*x].level == level);
+                            let reductions = tables.get_reductions(stack[*node].state, &token.tag);
+                            for rule in reductions.into_iter().filter(|x| !x.right.is_empty()) {
+                                if *node == other {
+                                    queue.push((other, rule, Some(edge)));
+                                } else if is_above {
+                                    queue.push((*node, rule, None));
+                                }
+                            }
+                        }
+                    } else {
+                        stack.push(StackNode {
+                            state: *next,
+                            level,
+                            edges: vec![(prev, label)],
+                        });
+                        let other = stack.len() - 1;
+                        frontier.push(other);
+                        by_state.insert(*next, other);
+                        for rule in tables.get_reductions(*next, &token.tag) {
+                            queue.push((other, rule, None));
+                        }
+                    }
+                }
+            }
+
+            let mut next_frontier: Vec<usize> = Vec::new();
+            let mut leaf = None;
+            for node in &frontier {
+                let actions = tables.action.get(&(stack[*node].state, token.tag.clone()));
+                for action in actions.into_iter().flatten() {
+                    match action {
+                        LR1Action::Shift(state) => {
+                            let label = *leaf.get_or_insert_with(|| {
+                                forest.nodes.push(ForestNode::Leaf(token.clone()));
+                                forest.nodes.len() - 1
+                            });
+                            match next_frontier.iter().find(|x| stack[**x].state == *state) {
+                                Some(other) => stack[*other].edges.push((*node, label)),
+                                None => {
+                                    stack.push(StackNode {
+                                        state: *state,
+                                        level: level + 1,
+                                        edges: vec![(*node, label)],
+                                    });
+                                    next_frontier.push(stack.len() - 1);
+                                }
+                            }
+                        }
+                        LR1Action::Accept => {
+                            forest.root = err_on_none(stack[*node].edges.first(), token)?.1;
+                            return Ok(forest);
+                        }
+                        LR1Action::Reduce(_) => {}
+                    }
+                }
+            }
+            if next_frontier.is_empty() {
+                let states: Vec<i32> = frontier.iter().map(|x| stack[*x].state).collect();
+                return Err(ParseError {
+                    token: token.clone(),
+                    expected: tables.get_expected(&states),
+                });
+            }
+            frontier = next_frontier;
+            level += 1;
+        }
+    }
+
+    /// Returns every parse tree of the forest, derivations through cycles are skipped.
+    pub fn trees(&self) -> Vec<ParseTree<T>> {
+        self.get_trees(self.root, &mut vec![false; self.nodes.len()])
+    }
+
+    fn get_trees(&self, node: usize, visited: &mut Vec<bool>) -> Vec<ParseTree<T>> {
+        let (nterm, alternatives) = match &self.nodes[node] {
+            ForestNode::Leaf(token) => return vec![ParseTree::Leaf(token.clone())],
+            ForestNode::Internal(nterm, alternatives) => (nterm, alternatives),
+        };
+        if visited[node] {
+            return Vec::new();
+        }
+        visited[node] = true;
+        let mut res = Vec::new();
+        for children in alternatives {
+            let mut prefixes: Vec<Vec<ParseTree<T>>> = vec![Vec::new()];
+            for child in children {
+                let trees = self.get_trees(*child, visited);
+                prefixes = prefixes
+                    .iter()
+                    .flat_map(|prefix| {
+                        trees.iter().map(|tree| {
+                            let mut prefix = prefix.clone();
+                            prefix.push(tree.clone());
+                            prefix
+                        })
+                    })
+                    .collect();
+            }
+            res.extend(
+                prefixes
+                    .into_iter()
+                    .map(|x| ParseTree::from_children(nterm.clone(), x)),
+            );
+        }
+        visited[node] = false;
+        res
+    }
+
+    /// Returns true if some node of the forest has more than one derivation.
+    pub fn is_ambiguous(&self) -> bool {
+        self.nodes
+            .iter()
+            .any(|x| matches!(x, ForestNode::Internal(_, alternatives) if alternatives.len() > 1))
+    }
+}
+
+/// Paths of `length` edges from the vertex, with the vertex at the end of the path and
+/// the labels of the edges from left to right.
+fn get_paths(
+    stack: &[StackNode],
+    node: usize,
+    length: usize,
+    first_edge: Option<usize>,
+) -> Vec<(usize, Vec<usize>)> {
+    let mut res = Vec::new();
+    let mut paths = vec![(node, Vec::new())];
+    while let Some((cur, labels)) = paths.pop() {
+        if labels.len() == length {
+            let mut labels = labels;
+            labels.reverse();
+            res.push((cur, labels));
+            continue;
+        }
+        for (i, (prev, label)) in stack[cur].edges.iter().enumerate() {
+            if labels.is_empty() && first_edge.is_some_and(|x| x != i) {
+                continue;
+            }
+            let mut labels = labels.clone();
+            labels.push(*label);
+            paths.push((*prev, labels));
+        }
+    }
+    res
+}
+
+/// Position in a source text, `offset` is counted in bytes.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+impl Position {
+    fn advance(self, c: char) -> Position {
+        if c == '\n' {
+            Position {
+                line: self.line + 1,
+                column: 1,
+                offset: self.offset + 1,
+            }
+        } else {
+            Position {
+                line: self.line,
+                column: self.column + 1,
+                offset: self.offset + c.len_utf8(),
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Span {
+    pub begin: Position,
+    pub end: Position,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Lexeme {
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct LexError {
+    pub position: Position,
+}
+
+impl Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unexpected character at {}:{}",
+            self.position.line, self.position.column
+        )
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum LexerAction {
+    Token(Terminal),
+    Skip,
+}
+
+/// DFA of a lexer. Transitions of every state are sorted disjoint character ranges.
+#[derive(PartialEq, Eq, Debug)]
+pub struct LexerTables {
+    pub start: usize,
+    pub transitions: Vec<Vec<(char, char, usize)>>,
+    pub accept: Vec<Option<LexerAction>>,
+}
+
+impl LexerTables {
+    /// Splits the input into tokens by the longest match, the last token is `Finish`.
+    pub fn tokenize(&self, input: &str) -> Result<Vec<Token<Lexeme>>, LexError> {
+        self.tokens(input).collect()
+    }
+
+    /// Same as `tokenize`, but the tokens are matched lazily, one per `next()`.
+    pub fn tokens<'a>(&'a self, input: &'a str) -> TokenStream<'a> {
+        TokenStream {
+            tables: self,
+            input,
+            begin: Position {
+                line: 1,
+                column: 1,
+                offset: 0,
+            },
+            is_finished: false,
+        }
+    }
+}
+
+/// Iterator over the tokens of the input, ends after `Finish` or the first error.
+pub struct TokenStream<'a> {
+    tables: &'a LexerTables,
+    input: &'a str,
+    begin: Position,
+    is_finished: bool,
+}
+
+impl Iterator for TokenStream<'_> {
+    type Item = Result<Token<Lexeme>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+        let input = self.input;
+        while self.begin.offset < input.len() {
+            let begin = self.begin;
+            let mut state = self.tables.start;
+            let mut cur = begin;
+            let mut last = None;
+            for c in input[begin.offset..].chars() {
+                let edges = &self.tables.transitions[state];
+                let i = edges.partition_point(|(_, to, _)| *to < c);
+                match edges.get(i) {
+                    Some((from, _, next)) if *from <= c => state = *next,
+                    _ => break,
+                }
+                cur = cur.advance(c);
+                if let Some(action) = &self.tables.accept[state] {
+                    last = Some((action, cur));
+                }
+            }
+            let Some((action, end)) = last else {
+                self.is_finished = true;
+                return Some(Err(LexError { position: begin }));
+            };
+            self.begin = end;
+            if let LexerAction::Token(t) = action {
+                return Some(Ok(Token {
+                    tag: TerminalOrFinish::Terminal(t.clone()),
+                    attribute: Lexeme {
+                        text: String::from(&input[begin.offset..end.offset]),
+                        span: Span { begin, end },
+                    },
+                }));
+            }
+        }
+        self.is_finished = true;
+        Some(Ok(Token {
+            tag: TerminalOrFinish::Finish,
+            attribute: Lexeme {
+                text: String::new(),
+                span: Span {
+                    begin: self.begin,
+                    end: self.begin,
+                },
+            },
+        }))
+    }
+}
+
+//@START_PARSE_TABLES@
+
+pub fn get_parse_tables() -> ParseTables {
+    let action = [
+        (
+            (
+                39,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![Term::Terminal(Terminal(String::from("op")))],
+            }),
+        ),
+        (
+            (15, TerminalOrFinish::Terminal(Terminal(String::from("op")))),
+            LR1Action::Shift(39),
+        ),
+        (
+            (
+                18,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(46),
+        ),
+        (
+            (
+                33,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(63),
+        ),
+        (
+            (
+                39,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![Term::Terminal(Terminal(String::from("op")))],
+            }),
+        ),
+        (
+            (
+                50,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (27, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("T")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("assoc"))),
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Nonterminal(Nonterminal(String::from("L"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (
+                46,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Shift(56),
+        ),
+        (
+            (
+                65,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("Y")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                67,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Shift(51),
+        ),
+        (
+            (
+                56,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Shift(47),
+        ),
+        (
+            (
+                50,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                67,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(71),
+        ),
+        (
+            (
+                15,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                0,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Shift(59),
+        ),
+        (
+            (
+                10,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("I")),
+                right: vec![
+                    Term::Nonterminal(Nonterminal(String::from("X"))),
+                    Term::Nonterminal(Nonterminal(String::from("I"))),
+                ],
+            }),
+        ),
+        (
+            (
+                43,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(31),
+        ),
+        (
+            (45, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("R")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                47,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Shift(56),
+        ),
+        (
+            (
+                19,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("L")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("term"))),
+                    Term::Nonterminal(Nonterminal(String::from("L"))),
+                ],
+            }),
+        ),
+        (
+            (
+                31,
+                TerminalOrFinish::Terminal(Terminal(String::from("imp"))),
+            ),
+            LR1Action::Shift(20),
+        ),
+        (
+            (
+                21,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("Q")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("pr"))),
+                    Term::Terminal(Terminal(String::from("str"))),
+                ],
+            }),
+        ),
+        (
+            (
+                50,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                56,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Shift(56),
+        ),
+        (
+            (
+                5,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("Q")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("pr"))),
+                    Term::Terminal(Terminal(String::from("term"))),
+                ],
+            }),
+        ),
+        (
+            (31, TerminalOrFinish::Terminal(Terminal(String::from("sk")))),
+            LR1Action::Shift(24),
+        ),
+        (
+            (
+                63,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("T")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("imp"))),
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (37, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("term"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                0,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(71),
+        ),
+        (
+            (
+                39,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![Term::Terminal(Terminal(String::from("op")))],
+            }),
+        ),
+        (
+            (
+                65,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("Y")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                27,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("T")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("assoc"))),
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Nonterminal(Nonterminal(String::from("L"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (
+                49,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("T")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("tok"))),
+                    Term::Terminal(Terminal(String::from("term"))),
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (
+                11,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(0),
+        ),
+        (
+            (50, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                62,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                16,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(70),
+        ),
+        (
+            (17, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("T")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Nonterminal(Nonterminal(String::from("Y"))),
+                    Term::Nonterminal(Nonterminal(String::from("P"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (
+                31,
+                TerminalOrFinish::Terminal(Terminal(String::from("assoc"))),
+            ),
+            LR1Action::Shift(18),
+        ),
+        (
+            (
+                31,
+                TerminalOrFinish::Terminal(Terminal(String::from("atr"))),
+            ),
+            LR1Action::Shift(23),
+        ),
+        (
+            (
+                52,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Shift(7),
+        ),
+        (
+            (
+                52,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Shift(69),
+        ),
+        (
+            (
+                60,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                3,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(2),
+        ),
+        (
+            (
+                37,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("term"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                23,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Shift(57),
+        ),
+        (
+            (
+                59,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                2,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("A")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("ax"))),
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("N"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (
+                9,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(15),
+        ),
+        (
+            (
+                29,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Nonterminal(Nonterminal(String::from("I"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (0, TerminalOrFinish::Terminal(Terminal(String::from("str")))),
+            LR1Action::Shift(51),
+        ),
+        (
+            (
+                29,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Nonterminal(Nonterminal(String::from("I"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                66,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Shift(21),
+        ),
+        (
+            (
+                29,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Nonterminal(Nonterminal(String::from("I"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                51,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                59,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                37,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("term"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                0,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
+            ),
+            LR1Action::Shift(62),
+        ),
+        (
+            (
+                15,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                21,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("Q")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("pr"))),
+                    Term::Terminal(Terminal(String::from("str"))),
+                ],
+            }),
+        ),
+        (
+            (
+                38,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(34),
+        ),
+        (
+            (
+                15,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                66,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Shift(5),
+        ),
+        (
+            (1, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("R")),
+                right: vec![],
+            }),
+        ),
+        (
+            (51, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                67,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("I")),
+                right: vec![],
+            }),
+        ),
+        (
+            (59, TerminalOrFinish::Terminal(Terminal(String::from("op")))),
+            LR1Action::Shift(39),
+        ),
+        (
+            (8, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("S")),
+                right: vec![
+                    Term::Nonterminal(Nonterminal(String::from("A"))),
+                    Term::Nonterminal(Nonterminal(String::from("R"))),
+                ],
+            }),
+        ),
+        (
+            (10, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("I")),
+                right: vec![
+                    Term::Nonterminal(Nonterminal(String::from("X"))),
+                    Term::Nonterminal(Nonterminal(String::from("I"))),
+                ],
+            }),
+        ),
+        (
+            (
+                29,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Nonterminal(Nonterminal(String::from("I"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                35,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(17),
+        ),
+        (
+            (
+                6,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("N")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("N"))),
+                ],
+            }),
+        ),
+        (
+            (
+                46,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("L")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                53,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(27),
+        ),
+        (
+            (
+                1,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(31),
+        ),
+        (
+            (
+                36,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Shift(32),
+        ),
+        (
+            (
+                36,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("C")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                72,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("Y")),
+                right: vec![Term::Terminal(Terminal(String::from("code")))],
+            }),
+        ),
+        (
+            (
+                5,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("Q")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("pr"))),
+                    Term::Terminal(Terminal(String::from("term"))),
+                ],
+            }),
+        ),
+        (
+            (
+                51,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                22,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("Q")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                60,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                71,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
+            ),
+            LR1Action::Shift(62),
+        ),
+        (
+            (
+                67,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Shift(59),
+        ),
+        (
+            (
+                14,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(38),
+        ),
+        ((28, TerminalOrFinish::Finish), LR1Action::Accept),
+        (
+            (
+                40,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("P")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Nonterminal(Nonterminal(String::from("I"))),
+                    Term::Nonterminal(Nonterminal(String::from("Q"))),
+                    Term::Nonterminal(Nonterminal(String::from("C"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("P"))),
+                ],
+            }),
+        ),
+        (
+            (
+                60,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                61,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("N")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                56,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("L")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                0,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("I")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                57,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(42),
+        ),
+        (
+            (
+                60,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                62,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                62,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                71,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Shift(59),
+        ),
+        (
+            (62, TerminalOrFinish::Terminal(Terminal(String::from("op")))),
+            LR1Action::Shift(39),
+        ),
+        (
+            (
+                13,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("L")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Nonterminal(Nonterminal(String::from("L"))),
+                ],
+            }),
+        ),
+        (
+            (62, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                54,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(0),
+        ),
+        (
+            (15, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                39,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![Term::Terminal(Terminal(String::from("op")))],
+            }),
+        ),
+        (
+            (
+                59,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                29,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Nonterminal(Nonterminal(String::from("I"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (41, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("R")),
+                right: vec![
+                    Term::Nonterminal(Nonterminal(String::from("T"))),
+                    Term::Nonterminal(Nonterminal(String::from("R"))),
+                ],
+            }),
+        ),
+        (
+            (
+                39,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![Term::Terminal(Terminal(String::from("op")))],
+            }),
+        ),
+        (
+            (42, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("T")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("atr"))),
+                    Term::Terminal(Terminal(String::from("code"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (
+                4,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("T")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("sk"))),
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (
+                47,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("L")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                62,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                42,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("T")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("atr"))),
+                    Term::Terminal(Terminal(String::from("code"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (
+                31,
+                TerminalOrFinish::Terminal(Terminal(String::from("tok"))),
+            ),
+            LR1Action::Shift(52),
+        ),
+        (
+            (
+                50,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                44,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(49),
+        ),
+        (
+            (
+                51,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (43, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("R")),
+                right: vec![],
+            }),
+        ),
+        (
+            (60, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                71,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Shift(51),
+        ),
+        (
+            (2, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("A")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("ax"))),
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("N"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (4, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("T")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("sk"))),
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (22, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Shift(66),
+        ),
+        (
+            (
+                45,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(31),
+        ),
+        (
+            (26, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("T")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("tok"))),
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (
+                48,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(26),
+        ),
+        (
+            (63, TerminalOrFinish::Finish),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("T")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("imp"))),
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                ],
+            }),
+        ),
+        (
+            (
+                10,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("I")),
+                right: vec![
+                    Term::Nonterminal(Nonterminal(String::from("X"))),
+                    Term::Nonterminal(Nonterminal(String::from("I"))),
+                ],
+            }),
+        ),
+        (
+            (
+                46,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Shift(47),
+        ),
+        (
+            (
+                50,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                58,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(53),
+        ),
+        (
+            (59, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                61,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(34),
+        ),
+        (
+            (
+                20,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Shift(33),
+        ),
+        (
+            (
+                71,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(71),
+        ),
+        (
+            (
+                37,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("term"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
+        ),
+        (
+            (
+                62,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                68,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Shift(12),
+        ),
+        (
+            (
+                69,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Shift(44),
+        ),
+        (
+            (
+                60,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
             }),
         ),
         (
             (
-                4,
+                64,
                 TerminalOrFinish::Terminal(Terminal(String::from("close"))),
             ),
-            LR1Action::Shift(5),
+            LR1Action::Shift(11),
         ),
         (
             (
-                7,
-                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+                70,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
             ),
-            LR1Action::Shift(17),
+            LR1Action::Shift(14),
         ),
         (
-            (11, TerminalOrFinish::Finish),
+            (
+                15,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
             LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("R")),
+                left: Nonterminal(String::from("O")),
                 right: vec![],
             }),
         ),
         (
             (
-                17,
+                31,
                 TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
             ),
-            LR1Action::Shift(3),
+            LR1Action::Shift(65),
         ),
         (
             (
-                19,
+                59,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                11,
                 TerminalOrFinish::Terminal(Terminal(String::from("close"))),
             ),
             LR1Action::Reduce(Rule {
@@ -211,290 +2774,438 @@ pub fn get_parse_tables() -> ParseTables {
         ),
         (
             (
-                19,
-                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+                50,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
             ),
-            LR1Action::Shift(17),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
         ),
         (
             (
-                14,
-                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+                47,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
             ),
-            LR1Action::Shift(4),
+            LR1Action::Shift(47),
         ),
         (
             (
-                8,
-                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+                62,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
             ),
             LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("I")),
-                right: vec![
-                    Term::Terminal(Terminal(String::from("nterm"))),
-                    Term::Nonterminal(Nonterminal(String::from("I"))),
-                ],
+                left: Nonterminal(String::from("O")),
+                right: vec![],
             }),
         ),
         (
             (
-                20,
+                72,
                 TerminalOrFinish::Terminal(Terminal(String::from("close"))),
             ),
             LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("I")),
-                right: vec![],
+                left: Nonterminal(String::from("Y")),
+                right: vec![Term::Terminal(Terminal(String::from("code")))],
             }),
         ),
         (
-            (22, TerminalOrFinish::Finish),
-            LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("S")),
-                right: vec![
-                    Term::Nonterminal(Nonterminal(String::from("A"))),
-                    Term::Nonterminal(Nonterminal(String::from("R"))),
-                ],
-            }),
+            (12, TerminalOrFinish::Terminal(Terminal(String::from("ax")))),
+            LR1Action::Shift(16),
         ),
         (
             (
-                21,
-                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+                24,
+                TerminalOrFinish::Terminal(Terminal(String::from("str"))),
             ),
-            LR1Action::Shift(6),
+            LR1Action::Shift(25),
         ),
         (
             (
-                6,
-                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
+                59,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
             ),
-            LR1Action::Shift(7),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
         ),
         (
             (
-                7,
+                0,
                 TerminalOrFinish::Terminal(Terminal(String::from("close"))),
             ),
             LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("P")),
+                left: Nonterminal(String::from("I")),
                 right: vec![],
             }),
         ),
         (
             (
-                18,
+                17,
                 TerminalOrFinish::Terminal(Terminal(String::from("open"))),
             ),
-            LR1Action::Shift(10),
-        ),
-        (
-            (1, TerminalOrFinish::Finish),
             LR1Action::Reduce(Rule {
                 left: Nonterminal(String::from("T")),
                 right: vec![
                     Term::Terminal(Terminal(String::from("open"))),
                     Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Nonterminal(Nonterminal(String::from("Y"))),
                     Term::Nonterminal(Nonterminal(String::from("P"))),
                     Term::Terminal(Terminal(String::from("close"))),
                 ],
             }),
         ),
+        (
+            (39, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![Term::Terminal(Terminal(String::from("op")))],
+            }),
+        ),
+        ((30, TerminalOrFinish::Finish), LR1Action::Accept),
         (
             (
-                9,
+                67,
                 TerminalOrFinish::Terminal(Terminal(String::from("close"))),
             ),
-            LR1Action::Shift(19),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("I")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                67,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
+            ),
+            LR1Action::Shift(62),
         ),
         (
-            (5, TerminalOrFinish::Finish),
+            (67, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
             LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("A")),
-                right: vec![
-                    Term::Terminal(Terminal(String::from("open"))),
-                    Term::Terminal(Terminal(String::from("ax"))),
-                    Term::Terminal(Terminal(String::from("open"))),
-                    Term::Terminal(Terminal(String::from("nterm"))),
-                    Term::Terminal(Terminal(String::from("close"))),
-                    Term::Terminal(Terminal(String::from("close"))),
-                ],
+                left: Nonterminal(String::from("I")),
+                right: vec![],
             }),
         ),
         (
             (
-                17,
+                15,
                 TerminalOrFinish::Terminal(Terminal(String::from("term"))),
             ),
-            LR1Action::Shift(20),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
         ),
         (
-            (
-                0,
-                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
-            ),
-            LR1Action::Shift(1),
+            (51, TerminalOrFinish::Terminal(Terminal(String::from("op")))),
+            LR1Action::Shift(39),
         ),
-        ((2, TerminalOrFinish::Finish), LR1Action::Accept),
         (
             (
-                3,
+                59,
                 TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
             ),
-            LR1Action::Shift(3),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
         ),
         (
             (
-                15,
-                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+                29,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
             ),
-            LR1Action::Shift(16),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Nonterminal(Nonterminal(String::from("I"))),
+                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
         ),
         (
             (
-                3,
-                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+                32,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
             ),
-            LR1Action::Shift(20),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("C")),
+                right: vec![Term::Terminal(Terminal(String::from("code")))],
+            }),
         ),
         (
             (
-                20,
-                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
+                15,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
             ),
-            LR1Action::Shift(20),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
         ),
         (
             (
-                16,
-                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
+                65,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
             ),
-            LR1Action::Shift(14),
+            LR1Action::Shift(72),
         ),
         (
-            (13, TerminalOrFinish::Finish),
+            (49, TerminalOrFinish::Finish),
             LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("R")),
+                left: Nonterminal(String::from("T")),
                 right: vec![
-                    Term::Nonterminal(Nonterminal(String::from("T"))),
-                    Term::Nonterminal(Nonterminal(String::from("R"))),
+                    Term::Terminal(Terminal(String::from("open"))),
+                    Term::Terminal(Terminal(String::from("tok"))),
+                    Term::Terminal(Terminal(String::from("term"))),
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Terminal(Terminal(String::from("close"))),
                 ],
             }),
         ),
         (
             (
-                1,
+                71,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("I")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                26,
                 TerminalOrFinish::Terminal(Terminal(String::from("open"))),
             ),
             LR1Action::Reduce(Rule {
                 left: Nonterminal(String::from("T")),
                 right: vec![
                     Term::Terminal(Terminal(String::from("open"))),
-                    Term::Terminal(Terminal(String::from("nterm"))),
-                    Term::Nonterminal(Nonterminal(String::from("P"))),
+                    Term::Terminal(Terminal(String::from("tok"))),
+                    Term::Terminal(Terminal(String::from("str"))),
+                    Term::Terminal(Terminal(String::from("str"))),
                     Term::Terminal(Terminal(String::from("close"))),
                 ],
             }),
         ),
         (
             (
-                23,
-                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+                37,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
             ),
             LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("P")),
+                left: Nonterminal(String::from("X")),
                 right: vec![
-                    Term::Terminal(Terminal(String::from("open"))),
-                    Term::Nonterminal(Nonterminal(String::from("I"))),
-                    Term::Terminal(Terminal(String::from("close"))),
-                    Term::Nonterminal(Nonterminal(String::from("P"))),
+                    Term::Terminal(Terminal(String::from("term"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
                 ],
             }),
         ),
         (
             (
-                17,
+                38,
                 TerminalOrFinish::Terminal(Terminal(String::from("close"))),
             ),
             LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("I")),
+                left: Nonterminal(String::from("N")),
                 right: vec![],
             }),
         ),
         (
-            (21, TerminalOrFinish::Finish),
+            (
+                39,
+                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![Term::Terminal(Terminal(String::from("op")))],
+            }),
+        ),
+        (
+            (
+                51,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
             LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("R")),
+                left: Nonterminal(String::from("O")),
                 right: vec![],
             }),
         ),
         (
             (
-                20,
+                60,
                 TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
             ),
-            LR1Action::Shift(3),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("nterm"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
         ),
         (
-            (10, TerminalOrFinish::Terminal(Terminal(String::from("ax")))),
-            LR1Action::Shift(15),
+            (
+                37,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
+                right: vec![
+                    Term::Terminal(Terminal(String::from("term"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
+                ],
+            }),
         ),
         (
             (
-                5,
-                TerminalOrFinish::Terminal(Terminal(String::from("open"))),
+                34,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
+            ),
+            LR1Action::Shift(55),
+        ),
+        (
+            (
+                51,
+                TerminalOrFinish::Terminal(Terminal(String::from("nterm"))),
             ),
             LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("A")),
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
+        ),
+        (
+            (7, TerminalOrFinish::Terminal(Terminal(String::from("str")))),
+            LR1Action::Shift(48),
+        ),
+        (
+            (29, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("X")),
                 right: vec![
                     Term::Terminal(Terminal(String::from("open"))),
-                    Term::Terminal(Terminal(String::from("ax"))),
-                    Term::Terminal(Terminal(String::from("open"))),
-                    Term::Terminal(Terminal(String::from("nterm"))),
-                    Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("I"))),
                     Term::Terminal(Terminal(String::from("close"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
                 ],
             }),
         ),
         (
             (
-                12,
-                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+                37,
+                TerminalOrFinish::Terminal(Terminal(String::from("term"))),
             ),
             LR1Action::Reduce(Rule {
-                left: Nonterminal(String::from("I")),
+                left: Nonterminal(String::from("X")),
                 right: vec![
                     Term::Terminal(Terminal(String::from("term"))),
-                    Term::Nonterminal(Nonterminal(String::from("I"))),
+                    Term::Nonterminal(Nonterminal(String::from("O"))),
                 ],
             }),
         ),
         (
             (
-                11,
+                54,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("P")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                22,
+                TerminalOrFinish::Terminal(Terminal(String::from("code"))),
+            ),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("Q")),
+                right: vec![],
+            }),
+        ),
+        (
+            (0, TerminalOrFinish::Terminal(Terminal(String::from("pr")))),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("I")),
+                right: vec![],
+            }),
+        ),
+        (
+            (
+                55,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(61),
+        ),
+        (
+            (
+                25,
+                TerminalOrFinish::Terminal(Terminal(String::from("close"))),
+            ),
+            LR1Action::Shift(4),
+        ),
+        (
+            (
+                51,
                 TerminalOrFinish::Terminal(Terminal(String::from("open"))),
             ),
-            LR1Action::Shift(6),
+            LR1Action::Reduce(Rule {
+                left: Nonterminal(String::from("O")),
+                right: vec![],
+            }),
         ),
     ]
     .into_iter()
     .collect();
     let goto = [
-        ((18, Nonterminal(String::from("S"))), 2),
-        ((18, Nonterminal(String::from("A"))), 21),
-        ((21, Nonterminal(String::from("T"))), 11),
-        ((19, Nonterminal(String::from("P"))), 23),
-        ((17, Nonterminal(String::from("I"))), 9),
-        ((20, Nonterminal(String::from("I"))), 12),
-        ((11, Nonterminal(String::from("T"))), 11),
-        ((3, Nonterminal(String::from("I"))), 8),
-        ((11, Nonterminal(String::from("R"))), 13),
-        ((21, Nonterminal(String::from("R"))), 22),
-        ((7, Nonterminal(String::from("P"))), 0),
+        ((46, Nonterminal(String::from("L"))), 58),
+        ((11, Nonterminal(String::from("P"))), 40),
+        ((45, Nonterminal(String::from("T"))), 43),
+        ((43, Nonterminal(String::from("R"))), 41),
+        ((45, Nonterminal(String::from("R"))), 30),
+        ((62, Nonterminal(String::from("O"))), 60),
+        ((67, Nonterminal(String::from("X"))), 67),
+        ((61, Nonterminal(String::from("N"))), 6),
+        ((36, Nonterminal(String::from("C"))), 64),
+        ((67, Nonterminal(String::from("I"))), 10),
+        ((43, Nonterminal(String::from("T"))), 43),
+        ((65, Nonterminal(String::from("Y"))), 54),
+        ((59, Nonterminal(String::from("O"))), 37),
+        ((15, Nonterminal(String::from("O"))), 29),
+        ((68, Nonterminal(String::from("A"))), 1),
+        ((68, Nonterminal(String::from("S"))), 28),
+        ((1, Nonterminal(String::from("T"))), 43),
+        ((22, Nonterminal(String::from("Q"))), 36),
+        ((51, Nonterminal(String::from("O"))), 50),
+        ((71, Nonterminal(String::from("I"))), 9),
+        ((0, Nonterminal(String::from("X"))), 67),
+        ((71, Nonterminal(String::from("X"))), 67),
+        ((47, Nonterminal(String::from("L"))), 13),
+        ((1, Nonterminal(String::from("R"))), 8),
+        ((54, Nonterminal(String::from("P"))), 35),
+        ((38, Nonterminal(String::from("N"))), 3),
+        ((56, Nonterminal(String::from("L"))), 19),
+        ((0, Nonterminal(String::from("I"))), 22),
+    ]
+    .into_iter()
+    .collect();
+    let entries = [
+        (Nonterminal(String::from("R")), 45),
+        (Nonterminal(String::from("S")), 68),
     ]
     .into_iter()
     .collect();
     ParseTables {
-        start: 18,
+        start: 68,
+        entries,
         action,
         goto,
     }
@@ -0,0 +1,147 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::lalr::get_closure;
+use crate::parser::*;
+use crate::symbols::{Item, SymbolTable};
+use crate::{DetermenisticLR1Automaton, Grammar, LR0Item, NonDeterministicLR1Automaton};
+
+/// State of the automaton under construction: kernel items and their lookaheads.
+struct State {
+    kernel: Vec<LR0Item>,
+    lookaheads: Vec<BTreeSet<u32>>,
+}
+
+impl State {
+    fn get_items(&self) -> BTreeSet<Item> {
+        let mut res = BTreeSet::new();
+        for (item, lookaheads) in self.kernel.iter().zip(&self.lookaheads) {
+            res.extend(lookaheads.iter().map(|lookup| Item {
+                rule: item.rule,
+                position: item.position,
+                lookup: *lookup,
+            }));
+        }
+        res
+    }
+
+    fn contains(&self, lookaheads: &[BTreeSet<u32>]) -> bool {
+        self.lookaheads
+            .iter()
+            .zip(lookaheads)
+            .all(|(x, y)| x.is_superset(y))
+    }
+
+    /// Pager's weak compatibility: merging the states can't introduce conflicts that are
+    /// not in the canonical automaton.
+    fn is_compatible(&self, lookaheads: &[BTreeSet<u32>]) -> bool {
+        let own = &self.lookaheads;
+        for i in 0..own.len() {
+            for j in i + 1..own.len() {
+                let is_mixed =
+                    !own[i].is_disjoint(&lookaheads[j]) || !own[j].is_disjoint(&lookaheads[i]);
+                if is_mixed
+                    && own[i].is_disjoint(&own[j])
+                    && lookaheads[i].is_disjoint(&lookaheads[j])
+                {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl DetermenisticLR1Automaton {
+    /// Builds the automaton with Pager's practical general method: a new LR(1) state is
+    /// merged into an existing one with the same core if they are weakly compatible, so
+    /// states of the LALR automaton are split only where merging causes conflicts.
+    pub fn minimal_from_grammar(grammar: &Grammar) -> DetermenisticLR1Automaton {
+        let symbols = SymbolTable::from_grammar(grammar);
+        let first = NonDeterministicLR1Automaton::get_first_ids(&symbols);
+        // start states of the entries come first
+        let mut states: Vec<State> = NonDeterministicLR1Automaton::get_start_items(&symbols)
+            .iter()
+            .map(|x| State {
+                kernel: vec![LR0Item::from_item(x)],
+                lookaheads: vec![BTreeSet::from([x.lookup])],
+            })
+            .collect();
+        let entries = states.len();
+        let mut by_core: HashMap<Vec<LR0Item>, Vec<usize>> = states
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.kernel.clone(), vec![i]))
+            .collect();
+        let mut gotos: Vec<HashMap<SymbolId, usize>> = vec![HashMap::new(); entries];
+        let mut stack: Vec<usize> = (0..entries).collect();
+        while let Some(cur) = stack.pop() {
+            let items = get_closure(&symbols, &first, states[cur].get_items());
+            let mut next: BTreeMap<SymbolId, BTreeMap<LR0Item, BTreeSet<u32>>> = BTreeMap::new();
+            for item in items {
+                if let Some(symbol) = symbols.next_symbol(&item) {
+                    let kernel_item = LR0Item {
+                        rule: item.rule,
+                        position: item.position + 1,
+                    };
+                    let kernel = next.entry(symbol).or_default();
+                    kernel.entry(kernel_item).or_default().insert(item.lookup);
+                }
+            }
+            for (symbol, kernel) in next {
+                let (kernel, lookaheads): (Vec<LR0Item>, Vec<BTreeSet<u32>>) =
+                    kernel.into_iter().unzip();
+                let candidates = by_core.entry(kernel.clone()).or_default();
+                let target = if let Some(id) = candidates
+                    .iter()
+                    .find(|x| states[**x].contains(&lookaheads))
+                {
+                    *id
+                } else if let Some(id) = candidates
+                    .iter()
+                    .find(|x| states[**x].is_compatible(&lookaheads))
+                {
+                    // lookaheads of the successors grow too
+                    let state = &mut states[*id];
+                    for (own, other) in state.lookaheads.iter_mut().zip(lookaheads) {
+                        own.extend(other);
+                    }
+                    stack.push(*id);
+                    *id
+                } else {
+                    candidates.push(states.len());
+                    states.push(State { kernel, lookaheads });
+                    gotos.push(HashMap::new());
+                    stack.push(states.len() - 1);
+                    states.len() - 1
+                };
+                gotos[cur].insert(symbol, target);
+            }
+        }
+
+        // successors of states that were merged later may be left unreachable
+        let items: Vec<BTreeSet<Item>> = states
+            .iter()
+            .map(|x| get_closure(&symbols, &first, x.get_items()))
+            .collect();
+        let mut edges = HashMap::new();
+        let mut stack: Vec<usize> = (0..entries).collect();
+        while let Some(cur) = stack.pop() {
+            if edges.contains_key(&items[cur]) {
+                continue;
+            }
+            let adjacent = gotos[cur]
+                .iter()
+                .map(|(symbol, other)| (items[*other].clone(), *symbol))
+                .collect();
+            stack.extend(gotos[cur].values().copied());
+            edges.insert(items[cur].clone(), adjacent);
+        }
+        DetermenisticLR1Automaton {
+            symbols,
+            edges,
+            start: items[0].clone(),
+            entries: items[1..entries].to_vec(),
+            precedence: grammar.precedence.clone(),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+/// Regular expression of a token declaration. Character classes are stored
+/// as sorted disjoint inclusive ranges.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Regex {
+    Empty,
+    Class(Vec<(char, char)>),
+    Concat(Vec<Regex>),
+    Alternation(Vec<Regex>),
+    Star(Box<Regex>),
+    Plus(Box<Regex>),
+    Optional(Box<Regex>),
+}
+
+impl Regex {
+    /// Supported syntax: literals, `.`, `[a-z]`, `[^...]`, `(...)`, `|`, `*`, `+`, `?`
+    /// and escapes `\n`, `\t`, `\r`, `\d`, `\w`, `\s`, `\` followed by a punctuation character.
+    pub fn parse(pattern: &str) -> Result<Regex, String> {
+        let mut parser = RegexParser {
+            chars: pattern.chars().collect(),
+            index: 0,
+        };
+        let res = parser.parse_alternation()?;
+        match parser.peek() {
+            Some(c) => Err(format!("unexpected `{}` at position {}", c, parser.index)),
+            None => Ok(res),
+        }
+    }
+
+    pub fn literal(s: &str) -> Regex {
+        Regex::Concat(s.chars().map(|c| Regex::Class(vec![(c, c)])).collect())
+    }
+
+    pub fn is_nullable(&self) -> bool {
+        match self {
+            Regex::Empty | Regex::Star(_) | Regex::Optional(_) => true,
+            Regex::Class(_) => false,
+            Regex::Concat(v) => v.iter().all(|x| x.is_nullable()),
+            Regex::Alternation(v) => v.iter().any(|x| x.is_nullable()),
+            Regex::Plus(x) => x.is_nullable(),
+        }
+    }
+}
+
+struct RegexParser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl RegexParser {
+    fn parse_alternation(&mut self) -> Result<Regex, String> {
+        let mut res = vec![self.parse_concat()?];
+        while self.peek() == Some('|') {
+            self.index += 1;
+            res.push(self.parse_concat()?);
+        }
+        if res.len() == 1 {
+            return Ok(res.pop().unwrap());
+        }
+        Ok(Regex::Alternation(res))
+    }
+
+    fn parse_concat(&mut self) -> Result<Regex, String> {
+        let mut res = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            res.push(self.parse_repetition()?);
+        }
+        match res.len() {
+            0 => Ok(Regex::Empty),
+            1 => Ok(res.pop().unwrap()),
+            _ => Ok(Regex::Concat(res)),
+        }
+    }
+
+    fn parse_repetition(&mut self) -> Result<Regex, String> {
+        let mut res = self.parse_atom()?;
+        while let Some(c) = self.peek() {
+            res = match c {
+                '*' => Regex::Star(Box::new(res)),
+                '+' => Regex::Plus(Box::new(res)),
+                '?' => Regex::Optional(Box::new(res)),
+                _ => break,
+            };
+            self.index += 1;
+        }
+        Ok(res)
+    }
+
+    fn parse_atom(&mut self) -> Result<Regex, String> {
+        let position = self.index;
+        let c = self.next().unwrap();
+        match c {
+            '(' => {
+                let res = self.parse_alternation()?;
+                if self.next() != Some(')') {
+                    return Err(format!("unclosed group at position {}", position));
+                }
+                Ok(res)
+            }
+            '[' => self.parse_class(position),
+            '.' => Ok(Regex::Class(complement(&[('\n', '\n')]))),
+            '\\' => Ok(Regex::Class(self.parse_escape()?)),
+            '*' | '+' | '?' => Err(format!("nothing to repeat at position {}", position)),
+            ']' => Err(format!("unexpected `]` at position {}", position)),
+            c => Ok(Regex::Class(vec![(c, c)])),
+        }
+    }
+
+    fn parse_class(&mut self, position: usize) -> Result<Regex, String> {
+        let is_negated = self.peek() == Some('^');
+        if is_negated {
+            self.index += 1;
+        }
+        let mut ranges = Vec::new();
+        loop {
+            let from = match self.next() {
+                None => return Err(format!("unclosed character class at position {}", position)),
+                Some(']') => break,
+                Some('\\') => {
+                    let escaped = self.parse_escape()?;
+                    if escaped.len() != 1 || escaped[0].0 != escaped[0].1 {
+                        ranges.extend(escaped);
+                        continue;
+                    }
+                    escaped[0].0
+                }
+                Some(c) => c,
+            };
+            let is_range = self.peek() == Some('-') && self.chars.get(self.index + 1) != Some(&']');
+            if !is_range {
+                ranges.push((from, from));
+                continue;
+            }
+            self.index += 1;
+            let to = match self.next() {
+                Some('\\') => {
+                    let escaped = self.parse_escape()?;
+                    if escaped.len() != 1 || escaped[0].0 != escaped[0].1 {
+                        return Err(format!("invalid range end at position {}", self.index));
+                    }
+                    escaped[0].0
+                }
+                Some(c) => c,
+                None => return Err(format!("unclosed character class at position {}", position)),
+            };
+            if to < from {
+                return Err(format!("invalid range `{}-{}`", from, to));
+            }
+            ranges.push((from, to));
+        }
+        let ranges = normalize(ranges);
+        if is_negated {
+            return Ok(Regex::Class(complement(&ranges)));
+        }
+        Ok(Regex::Class(ranges))
+    }
+
+    fn parse_escape(&mut self) -> Result<Vec<(char, char)>, String> {
+        let Some(c) = self.next() else {
+            return Err(String::from("trailing backslash"));
+        };
+        let res = match c {
+            'n' => vec![('\n', '\n')],
+            't' => vec![('\t', '\t')],
+            'r' => vec![('\r', '\r')],
+            'd' => vec![('0', '9')],
+            'w' => vec![('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')],
+            's' => vec![('\t', '\r'), (' ', ' ')],
+            c if c.is_alphanumeric() => return Err(format!("unknown escape `\\{}`", c)),
+            c => vec![(c, c)],
+        };
+        Ok(res)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let res = self.peek();
+        self.index += 1;
+        res
+    }
+}
+
+fn normalize(mut ranges: Vec<(char, char)>) -> Vec<(char, char)> {
+    ranges.sort();
+    let mut res: Vec<(char, char)> = Vec::new();
+    for (from, to) in ranges {
+        match res.last_mut() {
+            Some(last) if from as u32 <= last.1 as u32 + 1 => last.1 = last.1.max(to),
+            _ => res.push((from, to)),
+        }
+    }
+    res
+}
+
+/// All characters (without surrogates) that are not covered by sorted disjoint ranges.
+fn complement(ranges: &[(char, char)]) -> Vec<(char, char)> {
+    let mut res = Vec::new();
+    let mut begin = 0u32;
+    for (from, to) in ranges {
+        if begin < *from as u32 {
+            push_range(&mut res, begin, *from as u32 - 1);
+        }
+        begin = *to as u32 + 1;
+    }
+    if begin <= char::MAX as u32 {
+        push_range(&mut res, begin, char::MAX as u32);
+    }
+    res
+}
+
+fn push_range(res: &mut Vec<(char, char)>, from: u32, to: u32) {
+    const SURROGATES: (u32, u32) = (0xD800, 0xDFFF);
+    if from < SURROGATES.0 {
+        let end = to.min(SURROGATES.0 - 1);
+        res.push((char::from_u32(from).unwrap(), char::from_u32(end).unwrap()));
+    }
+    if to > SURROGATES.1 {
+        let begin = from.max(SURROGATES.1 + 1);
+        res.push((char::from_u32(begin).unwrap(), char::from_u32(to).unwrap()));
+    }
+}
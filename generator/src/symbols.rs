@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use crate::parser::*;
+use crate::{Grammar, LR1Item};
+
+/// Terminals, nonterminals and rules of a grammar numbered for the automata, so items
+/// are small copyable triples instead of owned strings. Terminal 0 is `Finish`.
+#[derive(Clone, Debug)]
+pub(crate) struct SymbolTable {
+    terminals: Vec<TerminalOrFinish>,
+    nonterminals: Vec<Nonterminal>,
+    terminal_ids: HashMap<Terminal, u32>,
+    nonterminal_ids: HashMap<Nonterminal, u32>,
+    rules: Vec<Rule>,
+    rights: Vec<Vec<SymbolId>>,
+    by_left: Vec<Vec<u32>>,
+}
+
+/// LR(1) item interned by `SymbolTable`: the index of the rule, the position of the dot
+/// and the id of the lookahead terminal.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, PartialOrd, Ord)]
+pub(crate) struct Item {
+    pub rule: u32,
+    pub position: u32,
+    pub lookup: u32,
+}
+
+impl SymbolTable {
+    pub fn from_grammar(grammar: &Grammar) -> SymbolTable {
+        let mut table = SymbolTable {
+            terminals: vec![TerminalOrFinish::Finish],
+            nonterminals: Vec::new(),
+            terminal_ids: HashMap::new(),
+            nonterminal_ids: HashMap::new(),
+            rules: grammar.rules.clone(),
+            rights: Vec::new(),
+            by_left: Vec::new(),
+        };
+        for (i, rule) in grammar.rules.iter().enumerate() {
+            let left = table.add_nonterminal(&rule.left);
+            table.by_left[left as usize].push(i as u32);
+            let right = rule
+                .right
+                .iter()
+                .map(|term| match term {
+                    Term::Terminal(t) => SymbolId::Terminal(table.add_terminal(t) as usize),
+                    Term::Nonterminal(n) => {
+                        SymbolId::Nonterminal(table.add_nonterminal(n) as usize)
+                    }
+                })
+                .collect();
+            table.rights.push(right);
+        }
+        table
+    }
+
+    fn add_terminal(&mut self, terminal: &Terminal) -> u32 {
+        if let Some(id) = self.terminal_ids.get(terminal) {
+            return *id;
+        }
+        let id = self.terminals.len() as u32;
+        self.terminals
+            .push(TerminalOrFinish::Terminal(terminal.clone()));
+        self.terminal_ids.insert(terminal.clone(), id);
+        id
+    }
+
+    fn add_nonterminal(&mut self, nonterminal: &Nonterminal) -> u32 {
+        if let Some(id) = self.nonterminal_ids.get(nonterminal) {
+            return *id;
+        }
+        let id = self.nonterminals.len() as u32;
+        self.nonterminals.push(nonterminal.clone());
+        self.nonterminal_ids.insert(nonterminal.clone(), id);
+        self.by_left.push(Vec::new());
+        id
+    }
+
+    pub fn get_terminal_id(&self, terminal: &Terminal) -> Option<u32> {
+        self.terminal_ids.get(terminal).copied()
+    }
+
+    pub fn get_nonterminal_id(&self, nonterminal: &Nonterminal) -> Option<u32> {
+        self.nonterminal_ids.get(nonterminal).copied()
+    }
+
+    pub fn terminal(&self, id: u32) -> &TerminalOrFinish {
+        &self.terminals[id as usize]
+    }
+
+    pub fn nonterminal(&self, id: u32) -> &Nonterminal {
+        &self.nonterminals[id as usize]
+    }
+
+    pub fn terminal_count(&self) -> usize {
+        self.terminals.len()
+    }
+
+    pub fn nonterminal_count(&self) -> usize {
+        self.nonterminals.len()
+    }
+
+    pub fn term(&self, symbol: SymbolId) -> Term {
+        match symbol {
+            SymbolId::Terminal(t) => match &self.terminals[t] {
+                TerminalOrFinish::Terminal(t) => Term::Terminal(t.clone()),
+                TerminalOrFinish::Finish => panic!("finish is not a grammar symbol"),
+            },
+            SymbolId::Nonterminal(n) => Term::Nonterminal(self.nonterminals[n].clone()),
+        }
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn rule(&self, id: u32) -> &Rule {
+        &self.rules[id as usize]
+    }
+
+    pub fn right(&self, rule: u32) -> &[SymbolId] {
+        &self.rights[rule as usize]
+    }
+
+    /// Indices of the rules of the nonterminal.
+    pub fn rules_of(&self, nonterminal: u32) -> &[u32] {
+        &self.by_left[nonterminal as usize]
+    }
+
+    pub fn is_finish(&self, item: &Item) -> bool {
+        item.position as usize == self.rights[item.rule as usize].len()
+    }
+
+    pub fn next_symbol(&self, item: &Item) -> Option<SymbolId> {
+        self.rights[item.rule as usize]
+            .get(item.position as usize)
+            .copied()
+    }
+
+    /// Converts the item back to rules and terminals for error messages.
+    pub fn resolve(&self, item: &Item) -> LR1Item {
+        LR1Item {
+            rule: self.rule(item.rule).clone(),
+            position: item.position,
+            lookup: self.terminal(item.lookup).clone(),
+        }
+    }
+}
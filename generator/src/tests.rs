@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::vec;
 
 use crate::*;
@@ -13,6 +14,7 @@ fn get_arithmetic_grammar() -> Grammar {
     // N  -> ( S )
     let mut grammar = Grammar {
         axiom: Nonterminal(String::from("S")),
+        entries: Vec::new(),
         rules: vec![
             // S  -> M Topt
             Rule {
@@ -73,6 +75,11 @@ fn get_arithmetic_grammar() -> Grammar {
                 ],
             },
         ],
+        precedence: Precedence::default(),
+        tokens: Vec::new(),
+        semantics: Semantics::default(),
+        spans: Vec::new(),
+        reserved: Vec::new(),
     };
     add_fake_axiom(&mut grammar);
     grammar
@@ -83,6 +90,7 @@ fn get_cbs_grammar() -> Grammar {
     // S -> (S)S
     let mut grammar = Grammar {
         axiom: Nonterminal(String::from("S")),
+        entries: Vec::new(),
         rules: vec![
             Rule {
                 left: Nonterminal(String::from("S")),
@@ -98,6 +106,11 @@ fn get_cbs_grammar() -> Grammar {
                 ],
             },
         ],
+        precedence: Precedence::default(),
+        tokens: Vec::new(),
+        semantics: Semantics::default(),
+        spans: Vec::new(),
+        reserved: Vec::new(),
     };
     add_fake_axiom(&mut grammar);
     grammar
@@ -133,6 +146,39 @@ fn test_deterministic_automaton_arithmetic_graphviz() {
     println!("{}", dfa.to_graphviz());
 }
 
+#[test]
+fn test_symbol_table() {
+    let grammar = get_cbs_grammar();
+    let symbols = SymbolTable::from_grammar(&grammar);
+    assert_eq!(symbols.terminal(0), &TerminalOrFinish::Finish);
+    let open = symbols
+        .get_terminal_id(&Terminal(String::from("(")))
+        .unwrap();
+    let s = symbols
+        .get_nonterminal_id(&Nonterminal(String::from("S")))
+        .unwrap();
+    assert_eq!(symbols.rules_of(s).len(), 2);
+    for rule in symbols.rules_of(s) {
+        assert_eq!(symbols.rule(*rule).left, Nonterminal(String::from("S")));
+    }
+    let rule = symbols
+        .rules()
+        .iter()
+        .position(|x| x.right.len() == 4)
+        .unwrap() as u32;
+    assert_eq!(symbols.right(rule)[0], SymbolId::Terminal(open as usize));
+    let item = Item {
+        rule,
+        position: 1,
+        lookup: 0,
+    };
+    assert_eq!(
+        symbols.next_symbol(&item),
+        Some(SymbolId::Nonterminal(s as usize))
+    );
+    assert_eq!(symbols.resolve(&item).to_string(), "S -> ( ^S ) S , $");
+}
+
 #[test]
 fn test_first_cbs() {
     let grammar = get_cbs_grammar();
@@ -231,6 +277,23 @@ fn test_first_arithmetic() {
     );
 }
 
+#[test]
+fn test_follow_cbs() {
+    let grammar = get_cbs_grammar();
+    let follow = calculate_follow(&grammar, &calculate_first(&grammar));
+    let expected: HashSet<TerminalOrFinish> = [
+        TerminalOrFinish::Finish,
+        TerminalOrFinish::Terminal(Terminal(String::from(")"))),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(follow[&Nonterminal(String::from("S"))], expected);
+    assert_eq!(
+        follow[&Nonterminal(String::from(GRAMMAR_AXIOM_NAME))],
+        HashSet::from([TerminalOrFinish::Finish])
+    );
+}
+
 #[test]
 fn test_tables_cbs() {
     let grammar = get_cbs_grammar();
@@ -317,26 +380,16 @@ fn test_parse_meta_grammar() {
     let dfa = DetermenisticLR1Automaton::from_non_deterministic(&nfa);
     println!("{}", dfa.to_graphviz());
     let tables = ParseTables::from_automaton(&dfa, ParseTablesType::LR1).unwrap();
-    let mut lexer = Lexer::new(
-        r#"
-                <axiom <S>>
-                <S <A R>>
-                <A <open ax open nterm close close>>
-                <R <T R>
-                    <>>
-                <T <open nterm P close>>
-                <P  <open I close P>
-                    <>>
-                <I  <term I>
-                    <nterm I>
-                    <>>
-            "#,
-    );
+    let mut sources = vec![Source::new(
+        "meta.txt",
+        include_str!("../../grammars/meta.txt"),
+    )];
+    let mut lexer = Lexer::new(&sources[0].text);
     let tokens = lexer.get_tokens();
     let tree = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
     println!("{}", tree.to_graphviz());
 
-    let parsed = get_grammar_from_tree(&tree).unwrap();
+    let parsed = get_grammar_from_tree(&tree, &mut sources).unwrap();
     assert_eq!(parsed, grammar);
 }
 
@@ -366,7 +419,38 @@ fn test_not_lr1() {
        <a S a>>";
     let res = ParseTables::from_string(input, ParseTablesType::LR1);
     let err = res.unwrap_err();
-    assert!(matches!(err, GeneratorError::ShiftReduceConflict));
+    match &err {
+        GeneratorError::ShiftReduceConflict(conflict) => {
+            let a = Terminal(String::from("a"));
+            let s = Nonterminal(String::from("S"));
+            assert_eq!(conflict.lookahead, TerminalOrFinish::Terminal(a.clone()));
+            assert_eq!(
+                conflict.shift,
+                vec![Rule {
+                    left: s.clone(),
+                    right: vec![
+                        Term::Terminal(a.clone()),
+                        Term::Nonterminal(s.clone()),
+                        Term::Terminal(a),
+                    ],
+                }]
+            );
+            assert_eq!(
+                conflict.reduce,
+                vec![Rule {
+                    left: s,
+                    right: vec![],
+                }]
+            );
+            assert!(!conflict.items.is_empty());
+        }
+        _ => panic!("expected shift-reduce conflict, got {:?}", err),
+    }
+    let message = err.to_string();
+    assert!(message.starts_with("Shift-reduce conflict in state"));
+    assert!(message.contains("on lookahead `a`"));
+    assert!(message.contains("shift `a` in rule S -> a S a"));
+    assert!(message.contains("reduce by rule S ->\n"));
 }
 
 #[test]
@@ -381,11 +465,264 @@ fn test_not_lalr() {
     <F <e>>";
     let res = ParseTables::from_string(input, ParseTablesType::LALR);
     let err = res.unwrap_err();
-    assert!(matches!(err, GeneratorError::ReduceReduceConflict));
+    match &err {
+        GeneratorError::ReduceReduceConflict(conflict) => {
+            let mut lefts: Vec<&str> = conflict.reduce.iter().map(|x| x.left.0.as_str()).collect();
+            lefts.sort();
+            assert_eq!(lefts, ["E", "F"]);
+        }
+        _ => panic!("expected reduce-reduce conflict, got {:?}", err),
+    }
+    assert!(err.to_string().contains("reduce by rule E -> e"));
     let res = ParseTables::from_string(input, ParseTablesType::LR1);
     assert!(res.is_ok());
 }
 
+#[test]
+fn test_counterexamples() {
+    let input = "
+    <axiom <S>>
+    <S <>
+       <a S a>>";
+    let err = ParseTables::from_string(input, ParseTablesType::LALR).unwrap_err();
+    let conflict = err.as_conflict().unwrap();
+    let examples = &conflict.counterexamples;
+    assert_eq!(examples.len(), 2);
+    assert_eq!(examples[0].action, ConflictAction::Shift);
+    assert_eq!(examples[1].action, ConflictAction::Reduce);
+    for example in examples {
+        assert_eq!(example.input, vec![Terminal(String::from("a"))]);
+    }
+    let message = err.to_string();
+    assert!(message.contains("shift : a • a\n    derivation: [S -> a [S -> • a S a] a]"));
+    assert!(message.contains("reduce: a • a\n    derivation: [S -> a [S -> •] a]"));
+    assert!(!message.contains("Note:"));
+
+    let input = "
+    <axiom <S>>
+    <S <a E a>
+       <b E b>
+       <a F b>
+       <b F a>>
+    <E <e>>
+    <F <e>>";
+    let err = ParseTables::from_string(input, ParseTablesType::LALR).unwrap_err();
+    let examples = &err.as_conflict().unwrap().counterexamples;
+    assert_eq!(examples.len(), 2);
+    assert_ne!(examples[0].input, examples[1].input);
+    assert!(err.to_string().contains("Note: the inputs differ"));
+
+    // the shorter path to the conflict goes through the unproductive `U`
+    let input = "
+    <axiom <S>>
+    <S <U A>
+       <b b A>>
+    <A <>
+       <a A a>>
+    <U <U u>>";
+    let err = ParseTables::from_string(input, ParseTablesType::LALR).unwrap_err();
+    let examples = &err.as_conflict().unwrap().counterexamples;
+    assert_eq!(examples.len(), 2);
+    for example in examples {
+        let input: Vec<&str> = example.input.iter().map(|x| x.0.as_str()).collect();
+        assert_eq!(input, ["b", "b", "a"]);
+    }
+
+    let input = "
+    <axiom <S>>
+    <S <U A>>
+    <A <>
+       <a A a>>
+    <U <U u>>";
+    let err = ParseTables::from_string(input, ParseTablesType::LALR).unwrap_err();
+    assert!(err.as_conflict().unwrap().counterexamples.is_empty());
+    assert!(err
+        .to_string()
+        .contains("reachable only through nonterminals"));
+}
+
+#[test]
+fn test_counterexamples_time() {
+    // every pair of operators conflicts, the search must stay bounded
+    let operators: Vec<String> = (0..12).map(|x| format!("<E o{} E>", x)).collect();
+    let input = format!("<axiom <E>> <E {} <n>>", operators.join(" "));
+    let start = std::time::Instant::now();
+    for tables_type in [ParseTablesType::LALR, ParseTablesType::LR1] {
+        let err = ParseTables::from_string_all_conflicts(&input, tables_type).unwrap_err();
+        let GeneratorError::Conflicts(conflicts) = err else {
+            panic!("expected list of conflicts");
+        };
+        assert!(conflicts
+            .iter()
+            .all(|x| x.as_conflict().unwrap().counterexamples.len() == 2));
+    }
+    assert!(start.elapsed() < std::time::Duration::from_secs(10));
+}
+
+#[test]
+fn test_all_conflicts() {
+    let input = "
+    <axiom <S>>
+    <S <>
+       <a S a>
+       <b S b>
+       <E>>
+    <E <e>>
+    <F <e>>
+    <S <F>>";
+    let res = ParseTables::from_string(input, ParseTablesType::LALR);
+    assert!(res.unwrap_err().as_conflict().is_some());
+    let res = ParseTables::from_string_all_conflicts(input, ParseTablesType::LALR);
+    let conflicts = match res.unwrap_err() {
+        GeneratorError::Conflicts(conflicts) => conflicts,
+        err => panic!("expected list of conflicts, got {:?}", err),
+    };
+    let mut lookaheads: Vec<(bool, String)> = conflicts
+        .iter()
+        .map(|x| {
+            let conflict = x.as_conflict().unwrap();
+            (conflict.shift.is_empty(), conflict.lookahead.to_string())
+        })
+        .collect();
+    lookaheads.sort();
+    lookaheads.dedup();
+    assert_eq!(
+        lookaheads,
+        [
+            (false, String::from("a")),
+            (false, String::from("b")),
+            (true, String::from("$")),
+            (true, String::from("a")),
+            (true, String::from("b")),
+        ]
+    );
+    let res = ParseTables::from_string_all_conflicts(input, ParseTablesType::LR1);
+    assert!(matches!(res.unwrap_err(), GeneratorError::Conflicts(_)));
+
+    // all actions of a conflict are reported together, conflicts are sorted by state
+    let input = "
+    <axiom <S>>
+    <S <A a> <B a> <C a> <a a> <b D> <b E>>
+    <A <a>>
+    <B <a>>
+    <C <a>>
+    <D <>>
+    <E <>>";
+    let res = ParseTables::from_string_all_conflicts(input, ParseTablesType::LALR);
+    let conflicts = match res.unwrap_err() {
+        GeneratorError::Conflicts(conflicts) => conflicts,
+        err => panic!("expected list of conflicts, got {:?}", err),
+    };
+    let conflicts: Vec<&Conflict> = conflicts.iter().map(|x| x.as_conflict().unwrap()).collect();
+    assert_eq!(conflicts.len(), 2);
+    assert!(conflicts[0].state < conflicts[1].state);
+    let conflict = conflicts.iter().find(|x| !x.shift.is_empty()).unwrap();
+    let mut lefts: Vec<&str> = conflict.reduce.iter().map(|x| x.left.0.as_str()).collect();
+    lefts.sort();
+    assert_eq!(lefts, ["A", "B", "C"]);
+    assert_eq!(conflict.counterexamples.len(), 4);
+}
+
+#[test]
+fn test_precedence() {
+    let input = include_str!("../../grammars/calc_prec.txt");
+    for method in [ParseTablesType::LR1, ParseTablesType::LALR] {
+        let tables = ParseTables::from_string(input, method).unwrap();
+        let cases = [
+            (vec!["n", "+", "n", "*", "n"], "(n + (n * n))"),
+            (vec!["n", "*", "n", "+", "n"], "((n * n) + n)"),
+            (vec!["n", "-", "n", "-", "n"], "((n - n) - n)"),
+            (vec!["n", "^", "n", "^", "n"], "(n ^ (n ^ n))"),
+            (vec!["-", "n", "*", "n"], "((- n) * n)"),
+            (vec!["-", "n", "^", "n"], "((- n) ^ n)"),
+        ];
+        for (input, expected) in cases {
+            let tokens = strings_to_tokens(&input);
+            let tree = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
+            assert_eq!(tree_to_string(&tree), expected);
+        }
+    }
+}
+
+#[test]
+fn test_precedence_nonassoc() {
+    let input = "
+    <axiom <E>>
+    <nonassoc <==>>
+    <left <+>>
+    <E <E == E>
+       <E + E>
+       <n>>";
+    let tables = ParseTables::from_string(input, ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&["n", "==", "n", "+", "n"]);
+    let tree = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
+    assert_eq!(tree_to_string(&tree), "(n == (n + n))");
+    let tokens = strings_to_tokens(&["n", "==", "n", "==", "n"]);
+    let err = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap_err();
+    assert_eq!(
+        err.token.tag,
+        TerminalOrFinish::Terminal(Terminal(String::from("==")))
+    );
+}
+
+#[test]
+fn test_precedence_unresolved() {
+    let input = "
+    <axiom <E>>
+    <left <+>>
+    <E <E + E>
+       <E * E>
+       <n>>";
+    let err = ParseTables::from_string(input, ParseTablesType::LALR).unwrap_err();
+    let conflict = err.as_conflict().unwrap();
+    assert!(!conflict.shift.is_empty());
+}
+
+#[test]
+fn test_prec_without_level() {
+    let input = "<axiom <E>>\n<left <+>>\n<E <E + E>\n   <- E prec uminus>\n   <n>>\n";
+    let err = Grammar::from_string(input).unwrap_err();
+    assert_eq!(
+        err.render(input, "s.txt"),
+        "s.txt:4:14: Terminal uminus in prec has no precedence level
+  |
+4 |    <- E prec uminus>
+  |              ^^^^^^"
+    );
+    // the level may be declared after the rule
+    let input = "<axiom <E>>\n<E <- E prec uminus>\n   <n>>\n<nonassoc <uminus>>\n";
+    assert!(Grammar::from_string(input).is_ok());
+}
+
+#[test]
+fn test_contextual_keywords() {
+    let rules = |input: &str| -> Vec<String> {
+        let grammar = Grammar::from_string(input).unwrap();
+        let rules = grammar.rules.iter().filter(|x| x.left.0 == "S");
+        rules.map(|x| x.to_string()).collect()
+    };
+    // associativity is a keyword only at the head of a declaration
+    assert_eq!(
+        rules("<axiom <S>> <S <left x> <right>> <nonassoc <x>>"),
+        ["S -> left x", "S -> right"]
+    );
+    // `prec` is a keyword only before the last terminal of an alternative
+    assert_eq!(
+        rules("<axiom <S>> <S <prec> <prec x y> <a prec x>> <left <x prec>>"),
+        ["S -> prec", "S -> prec x y", "S -> a"]
+    );
+    // so are the declarations of tokens
+    assert_eq!(
+        rules("<axiom <S>> <S <token> <skip x>> <token x \"x\"> <skip \" \">"),
+        ["S -> token", "S -> skip x"]
+    );
+    assert_eq!(
+        rules("<axiom <S>> <attribute %{ u32 %}> <S <attribute>>"),
+        ["S -> attribute"]
+    );
+    assert_eq!(rules("<axiom <S>> <S <import>>"), ["S -> import"]);
+}
+
 #[test]
 fn test_undeclared_nterm_usage() {
     let input = "
@@ -394,12 +731,1065 @@ fn test_undeclared_nterm_usage() {
     let res = ParseTables::from_string(input, ParseTablesType::LR1);
     let err = res.unwrap_err();
     let ok = match err {
-        GeneratorError::UndeclaredNonterminal(nterm) => nterm.0 == "A",
+        GeneratorError::UndeclaredNonterminal(nterm, _) => nterm.0 == "A",
         _ => false,
     };
     assert!(ok);
 }
 
+#[test]
+fn test_error_locations() {
+    let input = "<axiom <S>>\n<S <a <b A>*>\n   <c>>\n";
+    let err = Grammar::from_string(input).unwrap_err();
+    assert_eq!(
+        err.render(input, "s.txt"),
+        "s.txt:2:10: Use of undeclared nonterminal: A
+  |
+2 | <S <a <b A>*>
+  |          ^"
+    );
+
+    let input = "<axiom <E>>\n<E <E + E>\n\t<n>>\n<token n \"[0-9\">\n";
+    let err = ParseTables::from_string(input, ParseTablesType::LALR).unwrap_err();
+    let rendered = err.render(input, "e.txt");
+    assert!(rendered.starts_with("e.txt:2:4: Shift-reduce conflict in state"));
+    assert!(rendered.contains(
+        "`+`:
+  |
+2 | <E <E + E>
+  |    ^^^^^^^
+  shift `+` in rule E -> E + E"
+    ));
+    let grammar = Grammar::from_string(input).unwrap();
+    let err = LexerTables::from_grammar(&grammar).unwrap_err();
+    assert!(err
+        .render(input, "e.txt")
+        .starts_with("e.txt:4:10: Invalid regular expression"));
+
+    let input = "<axiom <E>>\n<E <E + E>\n\t<n>> >";
+    let err = Grammar::from_string(input).unwrap_err();
+    assert_eq!(
+        err.render(input, "e.txt"),
+        "e.txt:3:7: Unexpected token close at 3:7-3:8, expected one of `open`, EOF
+  |
+3 | \t<n>> >
+  | \t     ^"
+    );
+}
+
+#[test]
+fn test_lints() {
+    let input = "
+    <axiom <S>>
+    <S <a B>
+       <a B>
+       <C>>
+    <B <b>
+       <B>>
+    <C <C c>>
+    <D <d>>
+    <E <E? S>>";
+    let grammar = Grammar::from_string(input).unwrap();
+    let lints: Vec<(Lint, String)> = grammar
+        .lint()
+        .into_iter()
+        .map(|x| (x.lint, x.to_string()))
+        .collect();
+    assert_eq!(
+        lints,
+        [
+            (
+                Lint::Unreachable,
+                "Nonterminal D is unreachable from the axiom [unreachable]"
+            ),
+            (
+                Lint::Unreachable,
+                "Nonterminal E is unreachable from the axiom [unreachable]"
+            ),
+            (
+                Lint::Unproductive,
+                "Nonterminal C derives no string of terminals [unproductive]"
+            ),
+            (
+                Lint::DuplicateAlternative,
+                "Rule S -> a B is declared more than once [duplicate]"
+            ),
+            (Lint::Cycle, "Rule B -> B derives its own left side [cycle]"),
+        ]
+        .map(|(lint, message)| (lint, String::from(message)))
+    );
+    let duplicate = &grammar.lint()[3];
+    assert!(duplicate
+        .render(input, "l.txt")
+        .starts_with("l.txt:4:8: Rule S -> a B"));
+
+    let grammar = Grammar::from_string("<axiom <S>> <S <A <c>*>> <A <A? A> <a>>").unwrap();
+    let cycles: Vec<String> = grammar.lint().iter().map(|x| x.message.clone()).collect();
+    assert_eq!(cycles, ["Rule A -> A<1>? A derives its own left side"]);
+
+    let mut grammar = Grammar {
+        axiom: Nonterminal(String::from("S")),
+        entries: Vec::new(),
+        rules: vec![rule("S", &["token", "S"]), rule("S", &[])],
+        precedence: Precedence::default(),
+        tokens: Vec::new(),
+        semantics: Semantics::default(),
+        spans: Vec::new(),
+        reserved: Vec::new(),
+    };
+    add_fake_axiom(&mut grammar);
+    let lints = grammar.lint();
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].lint, Lint::ReservedTerminal);
+    assert!(lints[0].fragment.is_none());
+
+    // bare words are reported, quoted terminals are not
+    let input = "<axiom <S>> <S <token S> <'skip' S> <<left>* 'skip'> <skip>>";
+    let lints = Grammar::from_string(input).unwrap().lint();
+    let messages: Vec<String> = lints.iter().map(|x| x.to_string()).collect();
+    assert_eq!(
+        messages,
+        [
+            "Terminal token is named like a reserved word [reserved]",
+            "Terminal left is named like a reserved word [reserved]",
+            "Terminal skip is named like a reserved word [reserved]",
+        ]
+    );
+    assert!(lints[1]
+        .render(input, "r.txt")
+        .starts_with("r.txt:1:39: Terminal left"));
+}
+
+#[test]
+fn test_root_collision() {
+    let grammar = Grammar::from_string(include_str!("../../grammars/root.txt")).unwrap();
+    let tokens = strings_to_tokens(&["n", "+", "n", ";", "n", ";"]);
+    let fake = Nonterminal(String::from(GRAMMAR_AXIOM_NAME));
+    for tables_type in [
+        ParseTablesType::LR1,
+        ParseTablesType::LALR,
+        ParseTablesType::MinimalLR1,
+        ParseTablesType::SLR,
+    ] {
+        let tables = ParseTables::from_grammar(&grammar, tables_type).unwrap();
+        assert!(tables.goto.keys().all(|(_, nterm)| *nterm != fake));
+        assert_eq!(tables.entries.keys().collect::<Vec<_>>(), [&grammar.axiom]);
+        let tree = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
+        assert_eq!(tree.as_internal().unwrap().0 .0, "S");
+        assert_eq!(tree_to_string(&tree), "(((n + n) ;) n ;)");
+    }
+    let tables = GLRParseTables::from_grammar(&grammar, ParseTablesType::LALR);
+    let forest = ParseForest::from_tables_and_tokens(&tables, &tokens).unwrap();
+    let trees: Vec<String> = forest.trees().iter().map(tree_to_string).collect();
+    assert_eq!(trees, ["(((n + n) ;) n ;)"]);
+}
+
+#[test]
+fn test_quoted_terminals() {
+    let grammar = Grammar::from_string(
+        r#"
+        // кавычки позволяют называть терминалы как угодно
+        <axiom <S>>
+        <left <"<" '>'>>
+        <token 'Id' "[a-z]+">
+        <S <S "<" S>
+           <S '>' S>
+           <"(" 'Id' ")">
+           <"\"" 'a\'b' "x\\y" 'tab\t'>
+           <"[" ","* "]">>"#,
+    )
+    .unwrap();
+    let mut quoted = rule("S", &["(", "id", ")"]);
+    quoted.right[1] = Term::Terminal(Terminal(String::from("Id")));
+    assert_eq!(
+        grammar.rules[..4],
+        [
+            rule("S", &["S", "<", "S"]),
+            rule("S", &["S", ">", "S"]),
+            quoted,
+            rule("S", &["\"", "a'b", "x\\y", "tab\t"]),
+        ]
+    );
+    assert_eq!(
+        grammar.tokens[0].terminal,
+        Some(Terminal(String::from("Id")))
+    );
+    assert!(grammar
+        .precedence
+        .terminals
+        .contains_key(&Terminal(String::from(">"))));
+    let tables = ParseTables::from_grammar(&grammar, ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&["(", "Id", ")", "<", "[", ",", ",", "]"]);
+    let tree = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
+    assert_eq!(tree_to_string(&tree), "((( Id )) < ([ (, ,) ]))");
+    let source = tables.to_rust_source();
+    assert!(source.contains(r#"Terminal(String::from("\""))"#));
+    assert!(source.contains(r#"Terminal(String::from("x\\y"))"#));
+
+    // a quote always opens a literal, spaces included
+    let grammar = Grammar::from_string("<axiom <S>> <S <' x' ' ' a // comment\n>>").unwrap();
+    assert_eq!(grammar.rules[0], rule("S", &[" x", " ", "a"]));
+    let input = "<axiom <S>>\n<S <a 'b>>\n<T <c>>";
+    let err = Grammar::from_string(input).unwrap_err();
+    assert_eq!(
+        err.render(input, "u.txt"),
+        "u.txt:2:7: Unterminated literal
+  |
+2 | <S <a 'b>>
+  |       ^"
+    );
+}
+
+#[test]
+fn test_imports() {
+    let mut sources = vec![Source::new(
+        "../grammars/imports/main.txt",
+        include_str!("../../grammars/imports/main.txt"),
+    )];
+    let grammar = Grammar::from_sources(&mut sources).unwrap();
+    let names: Vec<&str> = sources.iter().map(|x| x.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "../grammars/imports/main.txt",
+            "../grammars/imports/stmt.txt",
+            "../grammars/imports/expr.txt",
+        ]
+    );
+    assert!(grammar.rules.contains(&rule("E", &["E", "+", "E"])));
+    assert_eq!(grammar.spans[grammar.rules.len() - 2].rule.file, 2);
+    let tables = ParseTables::from_grammar(&grammar, ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&["id", "=", "n", "+", "n", ";", "id", "=", "n", ";"]);
+    let tree = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
+    assert_eq!(tree_to_string(&tree), "((id = (n + n) ;) (id = n ;))");
+
+    let input = "<axiom <E>> <import \"../grammars/imports/expr.txt\"> <left <+>> <E <n>>";
+    let mut sources = vec![Source::new("p.txt", input)];
+    let err = Grammar::from_sources(&mut sources).unwrap_err();
+    assert_eq!(
+        err.render_sources(&sources),
+        "../grammars/imports/expr.txt:1:8: Precedence of terminal + is declared more than once
+  |
+1 | <left <+>>
+  |        ^
+p.txt:1:60: previous definition is here
+  |
+1 | <axiom <E>> <import \"../grammars/imports/expr.txt\"> <left <+>> <E <n>>
+  |                                                            ^"
+    );
+    // rules of a nonterminal are not merged between files
+    let input = "<axiom <E>> <import \"../grammars/imports/expr.txt\"> <E <m>>";
+    let mut sources = vec![Source::new("p.txt", input)];
+    let err = Grammar::from_sources(&mut sources).unwrap_err();
+    assert_eq!(
+        err.render_sources(&sources),
+        "../grammars/imports/expr.txt:2:2: Nonterminal E is declared more than once
+  |
+2 | <E <E + E>
+  |  ^
+p.txt:1:54: previous definition is here
+  |
+1 | <axiom <E>> <import \"../grammars/imports/expr.txt\"> <E <m>>
+  |                                                      ^"
+    );
+    let err = Grammar::from_string("<axiom <E>> <import \"missing.txt\"> <E <n>>").unwrap_err();
+    let GeneratorError::ImportError { path, fragment, .. } = err else {
+        panic!("expected import error, got {:?}", err);
+    };
+    assert_eq!(path, "missing.txt");
+    assert_eq!(fragment.unwrap().begin.column, 21);
+}
+
+const EBNF_GRAMMAR: &str = "
+    <axiom <S>>
+    <S <a B? <c B>* <d>+>>
+    <B <b>
+       <B * b>>";
+
+#[test]
+fn test_ebnf_desugaring() {
+    let mut lexer = Lexer::new(EBNF_GRAMMAR);
+    let tokens = lexer.get_tokens();
+    let tree = ParseTree::from_tables_and_tokens(&parser::get_parse_tables(), &tokens).unwrap();
+    let mut grammar = Grammar {
+        axiom: Nonterminal(String::from("S")),
+        entries: Vec::new(),
+        rules: vec![
+            rule("S<1>?", &[]),
+            rule("S<1>?", &["B"]),
+            rule("S<2>", &["c", "B"]),
+            rule("S<3>*", &[]),
+            rule("S<3>*", &["S<2>", "S<3>*"]),
+            rule("S<4>*", &[]),
+            rule("S<4>*", &["d", "S<4>*"]),
+            rule("S<4>+", &["d", "S<4>*"]),
+            rule("S", &["a", "S<1>?", "S<3>*", "S<4>+"]),
+            rule("B", &["b"]),
+            rule("B", &["B", "*", "b"]),
+        ],
+        precedence: Precedence::default(),
+        tokens: Vec::new(),
+        semantics: Semantics::default(),
+        spans: Vec::new(),
+        reserved: Vec::new(),
+    };
+    add_fake_axiom(&mut grammar);
+    let mut sources = vec![Source::new("ebnf.txt", EBNF_GRAMMAR)];
+    assert_eq!(get_grammar_from_tree(&tree, &mut sources).unwrap(), grammar);
+}
+
+#[test]
+fn test_ebnf_lists_are_flattened() {
+    let tables = ParseTables::from_string(EBNF_GRAMMAR, ParseTablesType::LR1).unwrap();
+    let tokens = strings_to_tokens(&["a", "c", "b", "c", "b", "*", "b", "d", "d", "d"]);
+    let tree = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
+    let (_, children) = tree.as_internal().unwrap();
+    let (name, optional) = children[1].as_internal().unwrap();
+    assert_eq!(name.0, "S<1>?");
+    assert!(optional.is_empty());
+    let (name, groups) = children[2].as_internal().unwrap();
+    assert_eq!(name.0, "S<3>*");
+    assert_eq!(groups.len(), 2);
+    assert_eq!(tree_to_string(&children[2]), "((c b) (c (b * b)))");
+    let (name, list) = children[3].as_internal().unwrap();
+    assert_eq!(name.0, "S<4>+");
+    assert_eq!(list.len(), 3);
+}
+
+const LEXER_GRAMMAR: &str = r##"
+    <axiom <S>>
+    <token id "[a-z_][a-z0-9_]*">
+    <token num "\d+">
+    <token str "\"([^\"\\]|\\.)*\"">
+    <skip "\s+">
+    <skip "#[^\n]*">
+    <S <Stmt*>>
+    <Stmt <let id = V ;>
+          <id = V ;>>
+    <V <num>
+       <id>
+       <str>>"##;
+
+#[test]
+fn test_generated_lexer() {
+    let grammar = Grammar::from_string(LEXER_GRAMMAR).unwrap();
+    let lexer = LexerTables::from_grammar(&grammar).unwrap();
+    let tokens = lexer
+        .tokenize("let x = 42; # comment\nlets = \"a\\\"b\";")
+        .unwrap();
+    let tags: Vec<String> = tokens.iter().map(|x| x.tag.to_string()).collect();
+    assert_eq!(
+        tags,
+        ["let", "id", "=", "num", ";", "id", "=", "str", ";", "$"]
+    );
+    assert_eq!(tokens[5].attribute.text, "lets");
+    assert_eq!(tokens[7].attribute.text, "\"a\\\"b\"");
+    let span = tokens[5].attribute.span;
+    assert_eq!((span.begin.line, span.begin.column), (2, 1));
+    assert_eq!(
+        (span.end.line, span.end.column, span.end.offset),
+        (2, 5, 26)
+    );
+
+    let tables = ParseTables::from_grammar(&grammar, ParseTablesType::LALR).unwrap();
+    ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
+
+    let err = lexer.tokenize("let x = 4 @").unwrap_err();
+    assert_eq!((err.position.line, err.position.column), (1, 11));
+}
+
+#[test]
+fn test_token_stream() {
+    let grammar = Grammar::from_string(LEXER_GRAMMAR).unwrap();
+    let lexer = LexerTables::from_grammar(&grammar).unwrap();
+    let tables = ParseTables::from_grammar(&grammar, ParseTablesType::LALR).unwrap();
+    let input = "let x = 42; y = \"s\";";
+    let tree = ParseTree::from_tables_and_token_iter(&tables, lexer.tokens(input)).unwrap();
+    let tokens = lexer.tokenize(input).unwrap();
+    let expected = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
+    assert_eq!(tree_to_string(&tree), tree_to_string(&expected));
+
+    let err = ParseTree::from_tables_and_token_iter(&tables, lexer.tokens("x = 4 @")).unwrap_err();
+    match err {
+        StreamError::Input(err) => assert_eq!(err.position.column, 7),
+        err => panic!("expected lexer error, got {:?}", err),
+    }
+    // the syntax error is found before the rest of the input is lexed
+    let err =
+        ParseTree::from_tables_and_token_iter(&tables, lexer.tokens("let let @")).unwrap_err();
+    match err {
+        StreamError::Parse(err) => assert_eq!(err.token.attribute.span.begin.column, 5),
+        err => panic!("expected syntax error, got {:?}", err),
+    }
+
+    // the end of an iterator without `Finish` is read as `Finish`
+    let mut tokens = lexer.tokenize(input).unwrap();
+    assert_eq!(tokens.pop().unwrap().tag, TerminalOrFinish::Finish);
+    let tree =
+        ParseTree::from_tables_and_token_iter(&tables, tokens.into_iter().map(Ok::<_, LexError>));
+    assert_eq!(tree_to_string(&tree.unwrap()), tree_to_string(&expected));
+    let mut tokens = lexer.tokenize("let x =").unwrap();
+    tokens.pop();
+    let err = tables
+        .parse_iter_with(tokens, |_| (), |_, _| ())
+        .unwrap_err();
+    match err {
+        StreamError::Parse(err) => {
+            assert_eq!(err.token.tag, TerminalOrFinish::Finish);
+            assert!(!err.expected.is_empty());
+        }
+        err => panic!("expected syntax error, got {:?}", err),
+    }
+    // an empty iterator has no attribute for `Finish`
+    let tokens = std::iter::empty::<Result<Token<Lexeme>, LexError>>();
+    let err = ParseTree::from_tables_and_token_iter(&tables, tokens).unwrap_err();
+    assert!(matches!(err, StreamError::Empty));
+    let err = tables
+        .parse_iter_with(Vec::<Token<()>>::new(), |_| (), |_, _| ())
+        .unwrap_err();
+    assert!(matches!(err, StreamError::Empty));
+
+    let tables = parser::get_parse_tables();
+    let count = tables
+        .parse_iter_with(
+            Lexer::new("<axiom <S>> <S <a>>"),
+            |_| 1,
+            |_, values| values.into_iter().sum::<usize>(),
+        )
+        .unwrap();
+    assert_eq!(count, 12);
+}
+
+#[test]
+fn test_generated_lexer_errors() {
+    let cases = [
+        (
+            "<token a \"(b\">",
+            "Invalid regular expression \"(b\": unclosed group at position 0",
+        ),
+        (
+            "<token a \"b*\">",
+            "Invalid regular expression \"b*\": matches the empty string",
+        ),
+        (
+            "<skip \"[z-a]\">",
+            "Invalid regular expression \"[z-a]\": invalid range `z-a`",
+        ),
+        (
+            "<token a \"b\"> <token a \"c\">",
+            "Token a is declared more than once",
+        ),
+    ];
+    for (declarations, expected) in cases {
+        let input = format!("<axiom <S>> {} <S <a>>", declarations);
+        let grammar = Grammar::from_string(&input).unwrap();
+        let err = LexerTables::from_grammar(&grammar).unwrap_err();
+        assert_eq!(err.to_string(), expected);
+    }
+}
+
+#[test]
+fn test_actions_to_rust_source() {
+    let input = "
+    <axiom <S>>
+    <attribute %{ String %}>
+    <S %{ usize %}
+       <id <, id>* %{ 1 + $2.len() %}>
+       <( S ) %{ $2 %}>>";
+    let grammar = Grammar::from_string(input).unwrap();
+    assert!(grammar.has_actions());
+    let source = grammar.actions_to_rust_source().unwrap();
+    assert!(source.contains("Token(String),"));
+    assert!(source.contains("S(usize),"));
+    assert!(source.contains("S_2__(Vec<(String, String)>),"));
+    assert!(source.contains("1 + _v2.len()"));
+    assert!(source.contains("_v2.reverse();"));
+    assert!(source.contains("tokens: &[Token<String>],"));
+}
+
+#[test]
+fn test_actions_errors() {
+    let input = "
+    <axiom <S>>
+    <S %{ usize %}
+       <a %{ 1 %}>
+       <b>>";
+    let grammar = Grammar::from_string(input).unwrap();
+    let err = grammar.actions_to_rust_source().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Missing action for rule S -> b of nonterminal with type"
+    );
+    let input = "
+    <axiom <S>>
+    <S <a S %{ $3 %}>
+       <>>";
+    let grammar = Grammar::from_string(input).unwrap();
+    let err = grammar.actions_to_rust_source().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid reference $3 in action of rule S -> a S"
+    );
+}
+
+const RECOVERY_GRAMMAR: &str = "
+    <axiom <P>>
+    <P <S*>>
+    <S <id = E ;>
+       <error ;>>
+    <E <E + n>
+       <n>>";
+
+#[test]
+fn test_error_recovery() {
+    let tables = ParseTables::from_string(RECOVERY_GRAMMAR, ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&[
+        "id", "=", "n", ";", "id", "n", "+", ";", "id", "=", "n", "+", "n", ";", "id", "=", "=",
+        ";",
+    ]);
+    let (tree, errors) = ParseTree::from_tables_and_tokens_with_recovery(&tables, &tokens);
+    let errors: Vec<String> = errors.iter().map(|x| x.token.tag.to_string()).collect();
+    assert_eq!(errors, ["n", "="]);
+    assert_eq!(
+        tree_to_string(&tree.unwrap()),
+        "((id = n ;) (error[n +] ;) (id = (n + n) ;) (error[=] ;))"
+    );
+}
+
+#[test]
+fn test_parse_error_expected() {
+    let tables = ParseTables::from_string(RECOVERY_GRAMMAR, ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&["id", "=", "n", "n"]);
+    let err = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap_err();
+    assert_eq!(err.expected_to_string(), "expected one of `+`, `;`");
+    let (_, errors) = ParseTree::from_tables_and_tokens_with_recovery(&tables, &tokens);
+    assert_eq!(errors[0].expected_to_string(), "expected one of `+`, `;`");
+    let err = Grammar::from_string("<axiom <S>> <S <a>>>").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Unexpected token close at 1:20-1:21, expected one of `open`, EOF"
+    );
+}
+
+#[test]
+fn test_error_recovery_fails() {
+    let tables = ParseTables::from_string(RECOVERY_GRAMMAR, ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&["id", "=", "n"]);
+    let (tree, errors) = ParseTree::from_tables_and_tokens_with_recovery(&tables, &tokens);
+    assert!(tree.is_none());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].token.tag, TerminalOrFinish::Finish);
+    let tokens = strings_to_tokens(&["id", "=", "n", ";"]);
+    let (tree, errors) = ParseTree::from_tables_and_tokens_with_recovery(&tables, &tokens);
+    assert!(errors.is_empty());
+    assert_eq!(tree_to_string(&tree.unwrap()), "(id = n ;)");
+}
+
+#[test]
+fn test_push_parser() {
+    let tables =
+        ParseTables::from_grammar(&get_arithmetic_grammar(), ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&["x", "+", "x", "*", "(", "x", ")"]);
+    let mut parser = PushParser::new(&tables);
+    for token in &tokens[..3] {
+        assert!(matches!(parser.push(token.clone()), PushResult::NeedMore));
+    }
+    let checkpoint = parser.clone();
+    // a rejected token leaves the parser as it was
+    let unexpected = strings_to_tokens(&["("]).remove(0);
+    match parser.push(unexpected) {
+        PushResult::Error(err) => {
+            assert_eq!(
+                err.expected_to_string(),
+                "expected one of `)`, `*`, `+`, EOF"
+            )
+        }
+        res => panic!("expected error, got {:?}", res),
+    }
+    for token in &tokens[3..tokens.len() - 1] {
+        assert!(matches!(parser.push(token.clone()), PushResult::NeedMore));
+    }
+    assert_eq!(
+        parser.get_expected(),
+        [term(")"), term("*"), term("+"), TerminalOrFinish::Finish]
+    );
+    let expected = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
+    match parser.push(tokens[tokens.len() - 1].clone()) {
+        PushResult::Accepted(tree) => assert_eq!(tree_to_string(&tree), tree_to_string(&expected)),
+        res => panic!("expected accepted tree, got {:?}", res),
+    }
+
+    let mut parser = checkpoint;
+    for token in strings_to_tokens(&["+", "x"]) {
+        if let PushResult::Accepted(tree) = parser.push(token) {
+            assert_eq!(tree_to_string(&tree), "((x ()) (+ (x ()) (+ (x ()) ())))");
+            return;
+        }
+    }
+    panic!("the input was not accepted");
+}
+
+#[test]
+fn test_entries() {
+    let input = r#"
+    <axiom <P> <S> <E> <S>>
+    <P <S P>
+       <>>
+    <S <id = E ;>>
+    <E <E + n>
+       <n>>
+    "#;
+    let grammar = Grammar::from_string(input).unwrap();
+    for tables_type in [
+        ParseTablesType::LR1,
+        ParseTablesType::LALR,
+        ParseTablesType::MinimalLR1,
+        ParseTablesType::SLR,
+    ] {
+        let tables = ParseTables::from_grammar(&grammar, tables_type).unwrap();
+        assert_eq!(tables.entries.len(), 3);
+        assert_eq!(tables.get_entry("P"), tables.start);
+
+        let tokens = strings_to_tokens(&["n", "+", "n"]);
+        let tree = ParseTree::from_tables_and_tokens_for(&tables, "E", &tokens).unwrap();
+        assert_eq!(tree_to_string(&tree), "(n + n)");
+        assert!(ParseTree::from_tables_and_tokens(&tables, &tokens).is_err());
+        let err = ParseTree::from_tables_and_tokens_for(&tables, "S", &tokens).unwrap_err();
+        assert_eq!(err.expected_to_string(), "expected `id`");
+
+        let tokens = strings_to_tokens(&["id", "=", "n", ";"]);
+        let tree = ParseTree::from_tables_and_tokens_for(&tables, "S", &tokens).unwrap();
+        assert_eq!(tree_to_string(&tree), "(id = n ;)");
+        assert!(ParseTree::from_tables_and_tokens(&tables, &tokens).is_ok());
+
+        let mut parser = PushParser::for_entry(&tables, "E");
+        assert_eq!(parser.get_expected(), [term("n")]);
+        for token in &strings_to_tokens(&["n", "+"])[..2] {
+            assert!(matches!(parser.push(token.clone()), PushResult::NeedMore));
+        }
+    }
+
+    let tables = ParseTables::from_grammar(&grammar, ParseTablesType::LALR).unwrap();
+    let source = tables.to_rust_source();
+    assert!(source.contains(&format!(
+        "(Nonterminal(String::from(\"S\")), {}),",
+        tables.get_entry("S")
+    )));
+}
+
+#[test]
+fn test_displace() {
+    let rows = vec![
+        vec![(0, 1), (2, 2), (3, 3)],
+        vec![],
+        vec![(1, 4), (4, 5)],
+        vec![(0, 6), (1, 7), (2, 8), (3, 9)],
+    ];
+    let (base, check, value) = dense::displace(&rows);
+    assert!(check.len() < 10);
+    for (row, entries) in rows.iter().enumerate() {
+        for column in 0..5 {
+            let index = base[row] + column;
+            let expected = entries.iter().find(|(x, _)| *x == column).map(|(_, v)| *v);
+            let actual = match check.get(index) {
+                Some(x) if *x == row as i32 => Some(value[index]),
+                _ => None,
+            };
+            assert_eq!(actual, expected);
+        }
+    }
+}
+
+#[test]
+fn test_dense_rust_source() {
+    let tables = ParseTables::from_string(RECOVERY_GRAMMAR, ParseTablesType::LALR).unwrap();
+    let source = tables.to_dense_rust_source_with(&[]);
+    let source = &source[source.find("//@START_PARSE_TABLES@").unwrap()..];
+    assert!(source.contains(r#"terminals: &["+", ";", "=", "error", "id", "n"],"#));
+    assert!(source.contains(r#"nonterminals: &["E", "P", "P<1>*", "S"],"#));
+    assert!(source.contains(
+        "DenseRule { left: 3, right: &[SymbolId::Terminal(4), SymbolId::Terminal(2)] },"
+    ));
+    assert!(source.contains("pub fn get_parse_tables() -> &'static DenseParseTables {"));
+    assert!(!source.contains("String::from"));
+
+    // every entry declared in `axiom` keeps its start state
+    let input = "<axiom <S> <E>> <S <E ;>> <E <n>>";
+    let tables = ParseTables::from_string(input, ParseTablesType::LALR).unwrap();
+    let source = tables.to_dense_rust_source_with(&[]);
+    assert!(source.contains(&format!(
+        r#"entries: &[("E", {}), ("S", {})],"#,
+        tables.get_entry("E"),
+        tables.get_entry("S")
+    )));
+}
+
+/// Dense tables with the same contents as the generated static arrays.
+fn get_dense_tables(tables: &ParseTables) -> &'static DenseParseTables {
+    let tables = tables.to_dense_tables();
+    let leak_table = |(base, check, value): dense::Displaced| DisplacedTable {
+        base: base.leak(),
+        check: check.leak(),
+        value: value.leak(),
+    };
+    let leak_names = |names: Vec<String>| -> &'static [&'static str] {
+        let names: Vec<&'static str> = names.into_iter().map(|x| &*x.leak()).collect();
+        names.leak()
+    };
+    let entries: Vec<(&'static str, usize)> = tables
+        .entries
+        .into_iter()
+        .map(|(x, state)| (&*x.leak(), state))
+        .collect();
+    let rules: Vec<DenseRule> = tables
+        .rules
+        .into_iter()
+        .map(|(left, right)| DenseRule {
+            left,
+            right: right.leak(),
+        })
+        .collect();
+    Box::leak(Box::new(DenseParseTables {
+        start: tables.start,
+        entries: entries.leak(),
+        terminals: leak_names(tables.terminals),
+        nonterminals: leak_names(tables.nonterminals),
+        rules: rules.leak(),
+        action: leak_table(tables.action),
+        goto: leak_table(tables.goto),
+    }))
+}
+
+#[test]
+fn test_dense_tables() {
+    let tables = ParseTables::from_string(RECOVERY_GRAMMAR, ParseTablesType::LALR).unwrap();
+    let dense = get_dense_tables(&tables);
+    let inputs: [&[&str]; 4] = [
+        &["id", "=", "n", "+", "n", ";", "id", "=", "n", ";"],
+        &[],
+        &["id", "=", "n", "n", ";"],
+        &["id", "=", "n", "+"],
+    ];
+    for input in inputs {
+        let tokens = strings_to_tokens(input);
+        let expected = ParseTree::from_tables_and_tokens(&tables, &tokens);
+        let actual = ParseTree::from_dense_tables_and_tokens(dense, &tokens);
+        match (expected, actual) {
+            (Ok(expected), Ok(actual)) => {
+                assert_eq!(tree_to_string(&actual), tree_to_string(&expected))
+            }
+            (Err(expected), Err(actual)) => {
+                assert_eq!(actual.token.tag, expected.token.tag);
+                assert_eq!(actual.expected, expected.expected);
+            }
+            (expected, actual) => panic!("expected {:?}, got {:?}", expected, actual),
+        }
+    }
+
+    let input = "<axiom <S> <E>> <S <E ;>> <E <E + n> <n>>";
+    let tables = ParseTables::from_string(input, ParseTablesType::LALR).unwrap();
+    let dense = get_dense_tables(&tables);
+    let tokens = strings_to_tokens(&["n", "+", "n"]);
+    let tree = ParseTree::from_dense_tables_and_tokens_for(dense, "E", &tokens).unwrap();
+    let expected = ParseTree::from_tables_and_tokens_for(&tables, "E", &tokens).unwrap();
+    assert_eq!(tree_to_string(&tree), tree_to_string(&expected));
+    assert!(ParseTree::from_dense_tables_and_tokens(dense, &tokens).is_err());
+}
+
+/// The `ast` module generated for `EBNF_GRAMMAR` with `--typed-ast` and formatted.
+mod ebnf_ast {
+    use crate::parser::*;
+
+    include!("tests/ebnf_ast.rs");
+}
+
+#[test]
+fn test_ast_to_rust_source() {
+    let grammar = Grammar::from_string(EBNF_GRAMMAR).unwrap();
+    let source = grammar.ast_to_rust_source();
+    // the checked in module is up to date, formatting aside
+    let normalize = |source: &str| -> String {
+        let res: String = source.split_whitespace().collect();
+        res.replace(",)", ")").replace(",]", "]").replace(",}", "}")
+    };
+    assert_eq!(
+        normalize(&source),
+        normalize(include_str!("tests/ebnf_ast.rs"))
+    );
+
+    // tokens named by their tag and position in the input
+    let to_tokens = |input: &[&str]| -> Vec<Token<Lexeme>> {
+        let position = Position {
+            line: 1,
+            column: 1,
+            offset: 0,
+        };
+        let lexeme = |text: String| Lexeme {
+            text,
+            span: Span {
+                begin: position,
+                end: position,
+            },
+        };
+        let mut tokens: Vec<Token<Lexeme>> = input
+            .iter()
+            .enumerate()
+            .map(|(i, x)| Token {
+                tag: TerminalOrFinish::Terminal(Terminal(x.to_string())),
+                attribute: lexeme(format!("{}{}", x, i)),
+            })
+            .collect();
+        tokens.push(Token {
+            tag: TerminalOrFinish::Finish,
+            attribute: lexeme(String::new()),
+        });
+        tokens
+    };
+    let tables = ParseTables::from_grammar(&grammar, ParseTablesType::LALR).unwrap();
+    let tokens = to_tokens(&["a", "b", "*", "b", "c", "b", "c", "b", "d", "d"]);
+    let tree = ParseTree::from_tables_and_tokens(&tables, &tokens).unwrap();
+    let ebnf_ast::ast::S::Alt0(a, b, pairs, ds) = ebnf_ast::ast::S::from_tree(tree).unwrap();
+    assert_eq!(a.attribute.text, "a0");
+    let Some(b) = b else {
+        panic!("expected B");
+    };
+    let ebnf_ast::ast::B::Alt1(inner, star, last) = *b else {
+        panic!("expected B * b, got {:?}", b);
+    };
+    assert!(matches!(*inner, ebnf_ast::ast::B::Alt0(ref x) if x.attribute.text == "b1"));
+    assert_eq!(
+        (star.attribute.text, last.attribute.text),
+        ("*2".into(), "b3".into())
+    );
+    let pairs: Vec<String> = pairs
+        .iter()
+        .map(|(c, b)| match &**b {
+            ebnf_ast::ast::B::Alt0(x) => format!("{} {}", c.attribute.text, x.attribute.text),
+            b => panic!("expected b, got {:?}", b),
+        })
+        .collect();
+    assert_eq!(pairs, ["c4 b5", "c6 b7"]);
+    let ds: Vec<&str> = ds.iter().map(|x| x.attribute.text.as_str()).collect();
+    assert_eq!(ds, ["d8", "d9"]);
+
+    // a tree of another grammar does not convert
+    let other = ParseTables::from_string("<axiom <S>> <S <b>>", ParseTablesType::LALR).unwrap();
+    let tree = ParseTree::from_tables_and_tokens(&other, &to_tokens(&["b"])).unwrap();
+    assert!(ebnf_ast::ast::S::from_tree(tree).is_none());
+}
+
+#[test]
+fn test_lalr_propagation() {
+    let grammars = [
+        get_cbs_grammar(),
+        get_arithmetic_grammar(),
+        get_meta_grammar(),
+        Grammar::from_string(include_str!("../../grammars/calc_prec.txt")).unwrap(),
+        Grammar::from_string(EBNF_GRAMMAR).unwrap(),
+        Grammar::from_string(RECOVERY_GRAMMAR).unwrap(),
+        // kernel items after the unproductive `C` get no lookaheads
+        Grammar::from_string("<axiom <A>> <A <a> <C> <c>> <B <c A a>> <C <C B C>>").unwrap(),
+    ];
+    for grammar in grammars {
+        let canonical = DetermenisticLR1Automaton::from_grammar(&grammar);
+        let lalr = DetermenisticLR1Automaton::lalr_from_grammar(&grammar);
+        let expected = tables_by_items(&canonical, ParseTablesType::LALR);
+        assert_eq!(tables_by_items(&lalr, ParseTablesType::LR1), expected);
+        assert_eq!(lalr.edges.len(), expected.1.len());
+    }
+}
+
+#[test]
+fn test_minimal_lr1() {
+    let input = include_str!("../../grammars/not_lalr.txt");
+    let grammar = Grammar::from_string(input).unwrap();
+    let tables = ParseTables::from_grammar(&grammar, ParseTablesType::MinimalLR1).unwrap();
+    for input in [
+        ["a", "e", "a"],
+        ["a", "e", "b"],
+        ["b", "e", "a"],
+        ["b", "e", "b"],
+    ] {
+        let tokens = strings_to_tokens(&input);
+        assert!(ParseTree::from_tables_and_tokens(&tables, &tokens).is_ok());
+    }
+    let states = |automaton: &DetermenisticLR1Automaton| automaton.edges.len();
+    let lalr = DetermenisticLR1Automaton::lalr_from_grammar(&grammar);
+    let minimal = DetermenisticLR1Automaton::minimal_from_grammar(&grammar);
+    // only the state after `a e` or `b e` is split
+    assert_eq!(states(&minimal), states(&lalr) + 1);
+
+    let grammars = [
+        get_arithmetic_grammar(),
+        get_meta_grammar(),
+        Grammar::from_string(include_str!("../../grammars/calc_prec.txt")).unwrap(),
+        Grammar::from_string(EBNF_GRAMMAR).unwrap(),
+    ];
+    for grammar in grammars {
+        let lalr = DetermenisticLR1Automaton::lalr_from_grammar(&grammar);
+        let minimal = DetermenisticLR1Automaton::minimal_from_grammar(&grammar);
+        assert_eq!(
+            tables_by_items(&minimal, ParseTablesType::MinimalLR1),
+            tables_by_items(&lalr, ParseTablesType::LR1)
+        );
+    }
+}
+
+#[test]
+fn test_slr_and_lr0() {
+    // reduces of `R -> L` on `=` are only excluded by LR(1) lookaheads
+    let input = "
+    <axiom <S>>
+    <S <L = R> <R>>
+    <L <* R> <id>>
+    <R <L>>";
+    assert!(ParseTables::from_string(input, ParseTablesType::LALR).is_ok());
+    let err = ParseTables::from_string(input, ParseTablesType::SLR).unwrap_err();
+    let conflict = err.as_conflict().unwrap();
+    assert_eq!(conflict.lookahead.to_string(), "=");
+
+    let grammar = get_cbs_grammar();
+    let tables = ParseTables::from_grammar(&grammar, ParseTablesType::SLR).unwrap();
+    let tokens = strings_to_tokens(&["(", "(", ")", ")", "(", ")"]);
+    assert!(ParseTree::from_tables_and_tokens(&tables, &tokens).is_ok());
+    let err = ParseTables::from_grammar(&grammar, ParseTablesType::LR0).unwrap_err();
+    assert!(matches!(err, GeneratorError::ShiftReduceConflict(_)));
+
+    let input = "
+    <axiom <S>>
+    <S <( S )> <x>>";
+    let tables = ParseTables::from_string(input, ParseTablesType::LR0).unwrap();
+    let tokens = strings_to_tokens(&["(", "(", "x", ")", ")"]);
+    assert!(ParseTree::from_tables_and_tokens(&tables, &tokens).is_ok());
+    let lalr = ParseTables::from_string(input, ParseTablesType::LALR).unwrap();
+    assert!(tables.action.len() > lalr.action.len());
+}
+
+#[test]
+fn test_glr() {
+    let input = include_str!("../../grammars/not_lr1.txt");
+    let tables = GLRParseTables::from_string(input, ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&["a", "a", "a", "a"]);
+    let forest = ParseForest::from_tables_and_tokens(&tables, &tokens).unwrap();
+    let trees: Vec<String> = forest.trees().iter().map(tree_to_string).collect();
+    assert_eq!(trees, ["(a (a () a) a)"]);
+    assert!(!forest.is_ambiguous());
+    let tokens = strings_to_tokens(&["a", "a", "a"]);
+    let err = ParseForest::from_tables_and_tokens(&tables, &tokens).unwrap_err();
+    assert_eq!(err.token.tag, TerminalOrFinish::Finish);
+
+    let input = "
+    <axiom <E>>
+    <E <E + E> <n>>";
+    let tables = GLRParseTables::from_string(input, ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&["n", "+", "n", "+", "n"]);
+    let forest = ParseForest::from_tables_and_tokens(&tables, &tokens).unwrap();
+    let mut trees: Vec<String> = forest.trees().iter().map(tree_to_string).collect();
+    trees.sort();
+    assert_eq!(trees, ["((n + n) + n)", "(n + (n + n))"]);
+    assert!(forest.is_ambiguous());
+
+    let input = "
+    <axiom <S>>
+    <S <A A>>
+    <A <a> <>>";
+    let tables = GLRParseTables::from_string(input, ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&["a"]);
+    let forest = ParseForest::from_tables_and_tokens(&tables, &tokens).unwrap();
+    let mut trees: Vec<String> = forest.trees().iter().map(tree_to_string).collect();
+    trees.sort();
+    assert_eq!(trees, ["(() a)", "(a ())"]);
+    let source = tables.to_rust_source_with(&[]);
+    assert!(source.contains("pub fn get_parse_tables() -> GLRParseTables"));
+}
+
+#[test]
+fn test_ll1() {
+    let grammar = get_arithmetic_grammar();
+    let tables = LL1ParseTables::from_grammar(&grammar).unwrap();
+    let lr_tables = ParseTables::from_grammar(&grammar, ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&["x", "+", "x", "*", "(", "x", "+", "x", ")"]);
+    let tree = ParseTree::from_ll1_tables_and_tokens(&tables, &tokens).unwrap();
+    let lr_tree = ParseTree::from_tables_and_tokens(&lr_tables, &tokens).unwrap();
+    assert_eq!(tree_to_string(&tree), tree_to_string(&lr_tree));
+
+    let tokens = strings_to_tokens(&["x", "+", "x", "*", "(", "x", "+", ")"]);
+    let err = ParseTree::from_ll1_tables_and_tokens(&tables, &tokens).unwrap_err();
+    assert_eq!(err.token.tag.to_string(), ")");
+    assert_eq!(err.expected_to_string(), "expected one of `(`, `x`");
+    let tokens = strings_to_tokens(&["x", "x"]);
+    let err = ParseTree::from_ll1_tables_and_tokens(&tables, &tokens).unwrap_err();
+    // FOLLOW of `Mopt` is predicted on its empty rule
+    assert_eq!(
+        err.expected_to_string(),
+        "expected one of `)`, `*`, `+`, EOF"
+    );
+
+    let input = "
+    <axiom <S>>
+    <S <a <b>? <c b>* <d>+>>";
+    let tables = LL1ParseTables::from_string(input).unwrap();
+    let lr_tables = ParseTables::from_string(input, ParseTablesType::LALR).unwrap();
+    let tokens = strings_to_tokens(&["a", "c", "b", "c", "b", "d", "d"]);
+    let tree = ParseTree::from_ll1_tables_and_tokens(&tables, &tokens).unwrap();
+    let lr_tree = ParseTree::from_tables_and_tokens(&lr_tables, &tokens).unwrap();
+    assert_eq!(tree_to_string(&tree), tree_to_string(&lr_tree));
+}
+
+#[test]
+fn test_ll1_conflicts() {
+    let input = "
+    <axiom <E>>
+    <E <E + n> <n>>";
+    let err = LL1ParseTables::from_string(input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "LL(1) conflict for nonterminal E on lookahead `n`:\n  predict rule E -> E + n\n  predict rule E -> n"
+    );
+    let input = "
+    <axiom <S>>
+    <S <A a> <b>>
+    <A <a> <>>";
+    let grammar = Grammar::from_string(input).unwrap();
+    match LL1ParseTables::from_grammar_all_conflicts(&grammar).unwrap_err() {
+        GeneratorError::Conflicts(conflicts) => {
+            assert_eq!(conflicts.len(), 1);
+            assert!(conflicts[0]
+                .to_string()
+                .contains("nonterminal A on lookahead `a`"));
+        }
+        err => panic!("expected list of conflicts, got {:?}", err),
+    }
+}
+
+type TablesByItems = (BTreeSet<Item>, BTreeMap<BTreeSet<Item>, Vec<String>>);
+
+/// Tables with states named by their items, so different numberings compare equal.
+fn tables_by_items(
+    automaton: &DetermenisticLR1Automaton,
+    tables_type: ParseTablesType,
+) -> TablesByItems {
+    let builder = TablesBuilder::new(automaton, tables_type);
+    let items = builder.items.clone();
+    let (tables, conflicts) = builder.build(true);
+    assert!(conflicts.is_empty());
+    let mut rows: BTreeMap<BTreeSet<Item>, Vec<String>> = BTreeMap::new();
+    for ((state, term), action) in &tables.action {
+        let action = match action {
+            LR1Action::Shift(next) => format!("shift {:?}", items[next]),
+            action => action.to_string(),
+        };
+        let row = rows.entry(items[state].clone()).or_default();
+        row.push(format!("{} {}", term, action));
+    }
+    for ((state, nterm), next) in &tables.goto {
+        let row = rows.entry(items[state].clone()).or_default();
+        row.push(format!("{} {:?}", nterm.0, items[next]));
+    }
+    rows.values_mut().for_each(|x| x.sort());
+    (items[&tables.start].clone(), rows)
+}
+
 fn strings_to_tokens(v: &[&str]) -> Vec<Token<()>> {
     v.iter()
         .map(|x| Token::<()> {
@@ -413,119 +1803,108 @@ fn strings_to_tokens(v: &[&str]) -> Vec<Token<()>> {
         .collect()
 }
 
+/// Prints a tree with every internal node of several children in parentheses.
+fn tree_to_string<T>(tree: &ParseTree<T>) -> String {
+    match tree {
+        ParseTree::Leaf(token) => token.tag.to_string(),
+        ParseTree::Error(tokens) => {
+            let tokens: Vec<String> = tokens.iter().map(|x| x.tag.to_string()).collect();
+            format!("error[{}]", tokens.join(" "))
+        }
+        ParseTree::Internal(_, children) => {
+            let children: Vec<String> = children.iter().map(tree_to_string).collect();
+            if children.len() == 1 {
+                children[0].clone()
+            } else {
+                format!("({})", children.join(" "))
+            }
+        }
+    }
+}
+
 fn get_meta_grammar() -> Grammar {
     let mut grammar = Grammar {
-        // <axiom <S>>
-        // <S <A R>>
-        // <A <open ax open nterm close close>>
-        // <R <T R>
-        //     <>>
-        // <T <open nterm P close>>
-        // <P  <open I close P>
-        //     <>>
-        // <I  <term I>
-        //     <nterm I>
-        //     <>>
         axiom: Nonterminal(String::from("S")),
+        entries: vec![Nonterminal(String::from("R"))],
         rules: vec![
-            // <S <A R>>
-            Rule {
-                left: Nonterminal(String::from("S")),
-                right: vec![
-                    Term::Nonterminal(Nonterminal(String::from("A"))),
-                    Term::Nonterminal(Nonterminal(String::from("R"))),
-                ],
-            },
-            // <A <open ax open nterm close close>>
-            Rule {
-                left: Nonterminal(String::from("A")),
-                right: vec![
-                    Term::Terminal(Terminal(String::from("open"))),
-                    Term::Terminal(Terminal(String::from("ax"))),
-                    Term::Terminal(Terminal(String::from("open"))),
-                    Term::Terminal(Terminal(String::from("nterm"))),
-                    Term::Terminal(Terminal(String::from("close"))),
-                    Term::Terminal(Terminal(String::from("close"))),
-                ],
-            },
-            // <R <T R>>
-            Rule {
-                left: Nonterminal(String::from("R")),
-                right: vec![
-                    Term::Nonterminal(Nonterminal(String::from("T"))),
-                    Term::Nonterminal(Nonterminal(String::from("R"))),
-                ],
-            },
-            // <R <>>
-            Rule {
-                left: Nonterminal(String::from("R")),
-                right: vec![],
-            },
-            // <T <open nterm P close>>
-            Rule {
-                left: Nonterminal(String::from("T")),
-                right: vec![
-                    Term::Terminal(Terminal(String::from("open"))),
-                    Term::Terminal(Terminal(String::from("nterm"))),
-                    Term::Nonterminal(Nonterminal(String::from("P"))),
-                    Term::Terminal(Terminal(String::from("close"))),
-                ],
-            },
-            // <P  <open I close P>>
-            Rule {
-                left: Nonterminal(String::from("P")),
-                right: vec![
-                    Term::Terminal(Terminal(String::from("open"))),
-                    Term::Nonterminal(Nonterminal(String::from("I"))),
-                    Term::Terminal(Terminal(String::from("close"))),
-                    Term::Nonterminal(Nonterminal(String::from("P"))),
-                ],
-            },
-            // <P <>>
-            Rule {
-                left: Nonterminal(String::from("P")),
-                right: vec![],
-            },
-            // <I  <term I>>
-            Rule {
-                left: Nonterminal(String::from("I")),
-                right: vec![
-                    Term::Terminal(Terminal(String::from("term"))),
-                    Term::Nonterminal(Nonterminal(String::from("I"))),
-                ],
-            },
-            // <I  <nterm I>>
-            Rule {
-                left: Nonterminal(String::from("I")),
-                right: vec![
-                    Term::Terminal(Terminal(String::from("nterm"))),
-                    Term::Nonterminal(Nonterminal(String::from("I"))),
-                ],
-            },
-            // <I  <>>
-            Rule {
-                left: Nonterminal(String::from("I")),
-                right: vec![],
-            },
+            rule("S", &["A", "R"]),
+            rule("A", &["open", "ax", "open", "nterm", "close", "N", "close"]),
+            rule("N", &["open", "nterm", "close", "N"]),
+            rule("N", &[]),
+            rule("R", &["T", "R"]),
+            rule("R", &[]),
+            rule("T", &["open", "nterm", "Y", "P", "close"]),
+            rule("T", &["open", "assoc", "open", "L", "close", "close"]),
+            rule("T", &["open", "tok", "term", "str", "close"]),
+            rule("T", &["open", "tok", "str", "str", "close"]),
+            rule("T", &["open", "sk", "str", "close"]),
+            rule("T", &["open", "atr", "code", "close"]),
+            rule("T", &["open", "imp", "str", "close"]),
+            rule("Y", &["code"]),
+            rule("Y", &[]),
+            rule("L", &["term", "L"]),
+            rule("L", &["str", "L"]),
+            rule("L", &[]),
+            rule("P", &["open", "I", "Q", "C", "close", "P"]),
+            rule("P", &[]),
+            rule("Q", &["pr", "term"]),
+            rule("Q", &["pr", "str"]),
+            rule("Q", &[]),
+            rule("C", &["code"]),
+            rule("C", &[]),
+            rule("I", &["X", "I"]),
+            rule("I", &[]),
+            rule("X", &["term", "O"]),
+            rule("X", &["str", "O"]),
+            rule("X", &["nterm", "O"]),
+            rule("X", &["open", "I", "close", "O"]),
+            rule("O", &["op"]),
+            rule("O", &[]),
         ],
+        precedence: Precedence::default(),
+        tokens: Vec::new(),
+        semantics: Semantics::default(),
+        spans: Vec::new(),
+        reserved: Vec::new(),
     };
     add_fake_axiom(&mut grammar);
     grammar
 }
 
+fn term(name: &str) -> TerminalOrFinish {
+    TerminalOrFinish::Terminal(Terminal(String::from(name)))
+}
+
+/// Builds a rule, names starting with a capital letter are nonterminals.
+fn rule(left: &str, right: &[&str]) -> Rule {
+    Rule {
+        left: Nonterminal(left.to_string()),
+        right: right
+            .iter()
+            .map(|x| {
+                if x.starts_with(char::is_uppercase) {
+                    Term::Nonterminal(Nonterminal(x.to_string()))
+                } else {
+                    Term::Terminal(Terminal(x.to_string()))
+                }
+            })
+            .collect(),
+    }
+}
+
 impl NonDeterministicLR1Automaton {
     fn to_graphviz(&self) -> String {
         let mut result = String::from("digraph G {\nrankdir=\"LR\"\n");
-        let mut ids: HashMap<&LR1Item, i32> = HashMap::new();
+        let mut ids: HashMap<&Item, i32> = HashMap::new();
         for (cur, item) in self.edges.keys().enumerate() {
-            let color = match item.is_finish() {
+            let color = match self.symbols.is_finish(item) {
                 true => "red",
                 false => "black",
             };
             result += format!(
                 r#"{} [label="{}", shape="rectangle", color="{}"]"#,
                 cur,
-                item.to_string(),
+                self.symbols.resolve(item),
                 color
             )
             .as_str();
@@ -535,12 +1914,12 @@ impl NonDeterministicLR1Automaton {
         result += "fake [style=\"invis\"]\n";
         result += format!("fake -> {}\n", ids[&self.start]).as_ref();
         for (item, adjacent) in &self.edges {
-            for (other_item, term) in adjacent {
+            for (other_item, symbol) in adjacent {
                 let id1 = ids[item];
                 let id2 = ids[other_item];
-                let term_str = match term {
-                    TermOrEmpty::Term(t) => t.to_string(),
-                    TermOrEmpty::Empty => String::from("EPS"),
+                let term_str = match symbol {
+                    Some(symbol) => self.symbols.term(*symbol).to_string(),
+                    None => String::from("EPS"),
                 };
                 result += format!(r#"{id1} -> {id2} [label="{term_str}"]"#).as_str();
                 result += "\n";
@@ -554,9 +1933,9 @@ impl NonDeterministicLR1Automaton {
 impl DetermenisticLR1Automaton {
     fn to_graphviz(&self) -> String {
         let mut result = String::from("digraph G {\nrankdir=\"LR\"\n");
-        let mut ids: HashMap<&BTreeSet<LR1Item>, i32> = HashMap::new();
+        let mut ids: HashMap<&BTreeSet<Item>, i32> = HashMap::new();
         for (cur, items) in self.edges.keys().enumerate() {
-            let end = items.iter().find(|x| x.is_finish());
+            let end = items.iter().find(|x| self.symbols.is_finish(x));
             let color = match end {
                 Some(_) => "red",
                 None => "black",
@@ -564,7 +1943,7 @@ impl DetermenisticLR1Automaton {
             result += format!(
                 "{} [shape=\"rectangle\",label=\"{}\", color=\"{}\"]\n",
                 cur,
-                Self::node_to_graphviz(items),
+                self.node_to_graphviz(items),
                 color
             )
             .as_ref();
@@ -573,20 +1952,21 @@ impl DetermenisticLR1Automaton {
         result += "fake [style=\"invis\"]\n";
         result += format!("fake -> {}\n", ids[&self.start]).as_ref();
         for (items, adjacent) in &self.edges {
-            for (other_items, term) in adjacent {
+            for (other_items, symbol) in adjacent {
                 let id1 = ids[items];
                 let id2 = ids[other_items];
-                result += format!("{} -> {} [label=\"{}\"]\n", id1, id2, term.to_string()).as_ref();
+                let term = self.symbols.term(*symbol);
+                result += format!("{} -> {} [label=\"{}\"]\n", id1, id2, term).as_ref();
             }
         }
         result += "}\n";
         result
     }
 
-    fn node_to_graphviz(items: &BTreeSet<LR1Item>) -> String {
-        items
-            .iter()
-            .fold(String::new(), |x, y| x + y.to_string().as_ref() + "\\n")
+    fn node_to_graphviz(&self, items: &BTreeSet<Item>) -> String {
+        items.iter().fold(String::new(), |x, y| {
+            x + self.symbols.resolve(y).to_string().as_ref() + "\\n"
+        })
     }
 }
 
@@ -595,7 +1975,7 @@ impl ParseTables {
         println!("Start: {}", self.start);
         println!("\nAction:");
         for ((state, term), action) in &self.action {
-            println!("({}x{})->{}", state, term.to_string(), action.to_string());
+            println!("({}x{})->{}", state, term, action);
         }
         println!("\nGoto:");
         for ((state, term), new_state) in &self.goto {
@@ -0,0 +1,141 @@
+#[allow(
+    non_camel_case_types,
+    non_snake_case,
+    dead_code,
+    clippy::enum_variant_names,
+    clippy::large_enum_variant
+)]
+pub mod ast {
+    use super::*;
+
+    /// `S<1>?`
+    fn convert_0(tree: super::ParseTree<Lexeme>) -> Option<Option<Box<B>>> {
+        let super::ParseTree::Internal(_, children) = tree else {
+            return None;
+        };
+
+        match children.into_iter().next() {
+            None => Some(None),
+            Some(child) => Some(Some(Box::new(B::from_tree(child)?))),
+        }
+    }
+
+    /// `S<2>`
+    fn convert_1(tree: super::ParseTree<Lexeme>) -> Option<(super::Token<Lexeme>, Box<B>)> {
+        let super::ParseTree::Internal(_, children) = tree else {
+            return None;
+        };
+        let mut children = children.into_iter();
+        Some((
+            leaf(children.next()?)?,
+            Box::new(B::from_tree(children.next()?)?),
+        ))
+    }
+
+    /// `S<3>*`
+    fn convert_2(tree: super::ParseTree<Lexeme>) -> Option<Vec<(super::Token<Lexeme>, Box<B>)>> {
+        let super::ParseTree::Internal(_, children) = tree else {
+            return None;
+        };
+        children.into_iter().map(convert_1).collect()
+    }
+
+    /// `S<4>*`
+    fn convert_3(tree: super::ParseTree<Lexeme>) -> Option<Vec<super::Token<Lexeme>>> {
+        let super::ParseTree::Internal(_, children) = tree else {
+            return None;
+        };
+        children.into_iter().map(leaf).collect()
+    }
+
+    /// `S<4>+`
+    fn convert_4(tree: super::ParseTree<Lexeme>) -> Option<Vec<super::Token<Lexeme>>> {
+        let super::ParseTree::Internal(_, children) = tree else {
+            return None;
+        };
+        children.into_iter().map(leaf).collect()
+    }
+
+    /// `S`
+    #[derive(Debug, Clone)]
+    pub enum S {
+        Alt0(
+            super::Token<Lexeme>,
+            Option<Box<B>>,
+            Vec<(super::Token<Lexeme>, Box<B>)>,
+            Vec<super::Token<Lexeme>>,
+        ),
+    }
+
+    impl S {
+        pub fn from_tree(tree: super::ParseTree<Lexeme>) -> Option<S> {
+            let super::ParseTree::Internal(nterm, children) = tree else {
+                return None;
+            };
+            if nterm.0 != "S" {
+                return None;
+            }
+            let symbols: Vec<String> = children.iter().map(symbol).collect();
+            let symbols: Vec<&str> = symbols.iter().map(String::as_str).collect();
+            let mut children = children.into_iter();
+            let res = match symbols[..] {
+                ["a", "S<1>?", "S<3>*", "S<4>+"] => S::Alt0(
+                    leaf(children.next()?)?,
+                    convert_0(children.next()?)?,
+                    convert_2(children.next()?)?,
+                    convert_4(children.next()?)?,
+                ),
+
+                _ => return None,
+            };
+            Some(res)
+        }
+    }
+
+    /// `B`
+    #[derive(Debug, Clone)]
+    pub enum B {
+        Alt0(super::Token<Lexeme>),
+        Alt1(Box<B>, super::Token<Lexeme>, super::Token<Lexeme>),
+    }
+
+    impl B {
+        pub fn from_tree(tree: super::ParseTree<Lexeme>) -> Option<B> {
+            let super::ParseTree::Internal(nterm, children) = tree else {
+                return None;
+            };
+            if nterm.0 != "B" {
+                return None;
+            }
+            let symbols: Vec<String> = children.iter().map(symbol).collect();
+            let symbols: Vec<&str> = symbols.iter().map(String::as_str).collect();
+            let mut children = children.into_iter();
+            let res = match symbols[..] {
+                ["b"] => B::Alt0(leaf(children.next()?)?),
+                ["B", "*", "b"] => B::Alt1(
+                    Box::new(B::from_tree(children.next()?)?),
+                    leaf(children.next()?)?,
+                    leaf(children.next()?)?,
+                ),
+
+                _ => return None,
+            };
+            Some(res)
+        }
+    }
+
+    fn symbol(tree: &super::ParseTree<Lexeme>) -> String {
+        match tree {
+            super::ParseTree::Internal(nterm, _) => nterm.0.clone(),
+            super::ParseTree::Leaf(token) => token.tag.to_string(),
+            super::ParseTree::Error(_) => String::from(super::ERROR_TERMINAL_NAME),
+        }
+    }
+
+    fn leaf(tree: super::ParseTree<Lexeme>) -> Option<super::Token<Lexeme>> {
+        match tree {
+            super::ParseTree::Leaf(token) => Some(token),
+            super::ParseTree::Internal(_, _) | super::ParseTree::Error(_) => None,
+        }
+    }
+}